@@ -0,0 +1,45 @@
+#!/usr/bin/env python3
+"""
+Builds and runs a framework's benchmarks once per feature set, e.g.
+
+    ./bench_features.py miden default multi-cpu --machine ubuntu-16-shared
+
+Each run is written to `.benchmarks/<machine>/features/<feature set>/`, where
+combine.py keeps the runs side by side, tagged with their feature set, and
+compares them with the `default` run.
+"""
+import argparse
+import os
+import socket
+import subprocess
+import sys
+
+# The feature set that builds without any features
+DEFAULT = 'default'
+
+def bench(crate_dir, feature_set, output_dir):
+    command = ['cargo', 'bench']
+    if feature_set != DEFAULT:
+        command += ['--features', feature_set]
+
+    env = dict(os.environ, BENCHY_OUTPUT_DIR=output_dir)
+    print(f"Running {' '.join(command)} in {crate_dir}, writing to {output_dir}", flush=True)
+    subprocess.run(command, cwd=crate_dir, env=env, check=True)
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__, formatter_class=argparse.RawDescriptionHelpFormatter)
+    parser.add_argument('crate_dir', help="directory of the framework's bench crate, e.g. miden")
+    parser.add_argument('feature_sets', nargs='+', help=f"comma separated cargo features to build with, or '{DEFAULT}'")
+    parser.add_argument('--machine', default=socket.gethostname(), help='name of the machine the results are filed under')
+    parser.add_argument('--output-dir', default='.benchmarks')
+    args = parser.parse_args()
+
+    for feature_set in args.feature_sets:
+        output_dir = os.path.abspath(os.path.join(args.output_dir, args.machine, 'features', feature_set))
+        try:
+            bench(args.crate_dir, feature_set, output_dir)
+        except subprocess.CalledProcessError as e:
+            sys.exit(f"Benchmarks with features '{feature_set}' failed with exit code {e.returncode}")
+
+if __name__ == "__main__":
+    main()
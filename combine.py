@@ -3,6 +3,19 @@ import os
 import json
 from datetime import datetime, timezone
 
+# Benchmarks that are named differently by some frameworks, but are compared
+# against each other
+COMPARED_AS = {
+    'SHA-3-256': 'SHA256',
+    'pedersen': 'Pedersen',
+}
+
+# Feature set whose results are shown for a framework that was only benchmarked
+# per feature set by bench_features.py
+SHOWN_FEATURES = {
+    'miden': 'multi-cpu',
+}
+
 def merge_benchmarks(benchmark_dir):
     out = {
         'meta': {
@@ -10,11 +23,15 @@ def merge_benchmarks(benchmark_dir):
         },
     }
     combined = {}
+    # feature set -> results laid out like `combined`
+    feature_sets = {}
+    case_meta = []
     
     for subdir, _, files in os.walk(benchmark_dir):
         if files:
             # Extracting category from directory name, e.g., "16-shared" or "metal"
-            category = os.path.basename(subdir)
+            category, feature_set = machine_and_features(subdir)
+            target = combined if feature_set is None else feature_sets.setdefault(feature_set, {})
             
             for file in files:
                 if file.endswith('.json'):  # Make sure it's a JSON file
@@ -28,25 +45,182 @@ def merge_benchmarks(benchmark_dir):
                                 out['meta'] = data
                                 continue
 
+                            # Per case metadata written by shared::report
+                            if file.endswith('.meta.json'):
+                                framework = file[:-len('.meta.json')]
+                                case_meta.append((target, framework, category, data))
+                                continue
+
                             if 'results' in data:  # Make sure 'results' key exists
                                 benchmark_name = os.path.splitext(file)[0]  # Removing .json extension to get benchmark name
                                 
                                 # Adding data to the combined dictionary
-                                if benchmark_name not in combined:
-                                    combined[benchmark_name] = {}
+                                if benchmark_name not in target:
+                                    target[benchmark_name] = {}
                                 
                                 results = data['results']
                                 results_map = {item["name"]: item for item in results}
 
-                                combined[benchmark_name][category] = results_map
+                                target[benchmark_name][category] = results_map
                             else:
                                 print(f"Warning: 'results' key not found in {filepath}")
                         except json.JSONDecodeError:
                             print(f"Warning: Could not decode JSON in {filepath}")
                 
+    for target, framework, category, data in case_meta:
+        apply_case_meta(target, framework, category, data)
+
+    show_feature_sets(combined, feature_sets)
+
+    out['meta']['inequivalent'] = flag_inequivalent_inputs(combined)
+    out['meta']['mismatchedSecurity'] = flag_mismatched_security(combined)
+    out['meta']['featureSpeedups'] = feature_speedups(feature_sets)
     out['frameworks'] = combined
+    out['featureSets'] = feature_sets
     return out
 
+def machine_and_features(subdir):
+    """
+    The machine a directory of results is for, and the feature set if it was
+    written by bench_features.py to `<machine>/features/<feature set>`.
+    """
+    parent = os.path.dirname(subdir)
+    if os.path.basename(parent) == 'features':
+        return os.path.basename(os.path.dirname(parent)), os.path.basename(subdir)
+    return os.path.basename(subdir), None
+
+def show_feature_sets(combined, feature_sets):
+    """
+    Shows the SHOWN_FEATURES results of frameworks that only have per feature
+    set results on a machine.
+    """
+    for framework, feature_set in SHOWN_FEATURES.items():
+        for category, benchmarks in feature_sets.get(feature_set, {}).get(framework, {}).items():
+            combined.setdefault(framework, {}).setdefault(category, benchmarks)
+
+def apply_case_meta(combined, framework, category, data):
+    benchmarks = combined.get(framework, {}).get(category, {})
+    for benchmark_name, cases in data.items():
+        benchmark = benchmarks.get(benchmark_name)
+        if benchmark is None:
+            continue
+        for result in benchmark['results']:
+            result.update(cases.get(result['name'], {}))
+
+def flag_inequivalent_inputs(combined):
+    """
+    Marks every case whose input differs from the input of the same case in
+    another framework (on the same machine), e.g. hashing constants instead of
+    private data, or SHA3 instead of SHA2.
+    """
+    inequivalent = []
+    for (category, benchmark_name, case), results in compared_cases(combined, 'input'):
+        fields = sorted({
+            field
+            for _, a in results
+            for _, b in results
+            for field in set(a['input']) | set(b['input'])
+            if a['input'].get(field) != b['input'].get(field)
+        })
+        for _, result in results:
+            result['equivalent'] = not fields
+        if fields:
+            print(f"Warning: {benchmark_name} {case} on {category} is not equivalent across frameworks, differs in {', '.join(fields)}")
+            inequivalent.append({
+                'machine': category,
+                'benchmark': benchmark_name,
+                'case': case,
+                'fields': fields,
+                'inputs': {framework: result['input'] for framework, result in results},
+            })
+
+    return inequivalent
+
+def flag_mismatched_security(combined):
+    """
+    Lists every case whose proofs claim a different conjectured security level
+    than the same case in another framework (on the same machine), since a
+    lower level is usually cheaper to prove. Only levels of the same kind are
+    compared, as a STARK's FRI conjecture says nothing about a SNARK's curve.
+    """
+    mismatched = []
+    for (category, benchmark_name, case), results in compared_cases(combined, 'security'):
+        by_kind = {}
+        for framework, result in results:
+            kind = result['security'].get('kind')
+            by_kind.setdefault(kind, {})[framework] = result['security']
+        for kind, levels in sorted(by_kind.items(), key=lambda item: str(item[0])):
+            if len({level.get('conjectured') for level in levels.values()}) > 1:
+                print(f"Warning: {benchmark_name} {case} on {category} is proven at different {kind} security levels across frameworks")
+                mismatched.append({
+                    'machine': category,
+                    'benchmark': benchmark_name,
+                    'case': case,
+                    'kind': kind,
+                    'security': levels,
+                })
+
+    return mismatched
+
+def feature_speedups(feature_sets):
+    """
+    Lists how much faster every case ran with each feature set than with the
+    default build of the same framework, on the same machine.
+    """
+    default = feature_sets.get('default', {})
+    speedups = []
+    for feature_set, frameworks in sorted(feature_sets.items()):
+        if feature_set == 'default':
+            continue
+        for framework, categories in sorted(frameworks.items()):
+            for category, benchmarks in sorted(categories.items()):
+                baseline = default.get(framework, {}).get(category, {})
+                for benchmark_name, benchmark in benchmarks.items():
+                    baseline_results = {
+                        result['name']: result
+                        for result in baseline.get(benchmark_name, {}).get('results', [])
+                    }
+                    for result in benchmark['results']:
+                        baseline_result = baseline_results.get(result['name'])
+                        # Cases that don't time anything, like the proof
+                        # compression ones, have no speedup
+                        if baseline_result is None or not timed(result) or not timed(baseline_result):
+                            continue
+                        speedups.append({
+                            'machine': category,
+                            'framework': framework,
+                            'features': feature_set,
+                            'benchmark': benchmark_name,
+                            'case': result['name'],
+                            'speedup': seconds(baseline_result['time']) / seconds(result['time']),
+                        })
+
+    return speedups
+
+def timed(result):
+    return 'time' in result and seconds(result['time']) > 0
+
+def seconds(time):
+    return time['secs'] + time['nanos'] / 1e9
+
+def compared_cases(combined, field):
+    """
+    Groups the results that have `field` by the (machine, benchmark, case) they
+    are compared as, sorted.
+    """
+    # (category, benchmark, case) -> [(framework, result)]
+    by_case = {}
+    for framework, categories in combined.items():
+        for category, benchmarks in categories.items():
+            for benchmark_name, benchmark in benchmarks.items():
+                compared_as = COMPARED_AS.get(benchmark_name, benchmark_name)
+                for result in benchmark['results']:
+                    if field in result:
+                        key = (category, compared_as, result['name'])
+                        by_case.setdefault(key, []).append((framework, result))
+
+    return sorted(by_case.items())
+
 if __name__ == "__main__":
     benchmark_dir = ".benchmarks"
     
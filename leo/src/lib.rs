@@ -1,6 +1,10 @@
 use leo_compiler::Compiler;
 use leo_errors::emitter::Handler;
 use leo_package::root::Env;
+use shared::{
+    input::{Hash, HashInput, Visibility},
+    security::Security,
+};
 use std::fs::{self, File};
 
 use snarkvm::{
@@ -14,6 +18,12 @@ use snarkvm::{
     },
 };
 
+/// snarkVM's Varuna proofs are over BLS12-377, which targets 128 bits of security
+/// against the best known discrete log attacks
+pub fn security() -> Security {
+    Security::discrete_log(128)
+}
+
 pub fn assert() -> impl FnOnce() -> Execution<Testnet3> {
     let run_and_prove = prepare(
         "asrt",
@@ -55,6 +65,13 @@ pub fn fib(n: u32) -> impl FnOnce() -> Execution<Testnet3> {
     run_and_prove
 }
 
+/// Each iteration hashes the same constant zero `u256` with one Keccak permutation
+pub fn sha_3_256_input(n_bytes: u32) -> HashInput {
+    let iterations = (n_bytes as f64 / (256. / 8.)).ceil() as usize;
+    HashInput::new(Hash::Sha3_256, iterations * 32, Visibility::Constant)
+        .with_compressions(iterations)
+}
+
 pub fn sha_3_256(n_bytes: u32) -> impl FnOnce() -> Execution<Testnet3> {
     let iterations = (n_bytes as f64 / (256. / 8.)).ceil(); // 32 bytes at a time
     let run_and_prove = prepare(
@@ -83,6 +100,12 @@ pub fn sha_3_256(n_bytes: u32) -> impl FnOnce() -> Execution<Testnet3> {
     run_and_prove
 }
 
+/// Each iteration hashes the same constant zero `u64`
+pub fn pedersen_128_input(n_bytes: u32) -> HashInput {
+    let iterations = (n_bytes as f64 / (64. / 8.)).ceil() as usize;
+    HashInput::new(Hash::Pedersen, iterations * 8, Visibility::Constant)
+}
+
 pub fn pedersen_128(n_bytes: u32) -> impl FnOnce() -> Execution<Testnet3> {
     let iterations = (n_bytes as f64 / (64. / 8.)).ceil(); // 8 bytes at a time
     let run_and_prove = prepare(
@@ -1,26 +1,51 @@
 extern crate miden_bench;
 
 use benchy::{benchmark, BenchmarkRun};
-use miden_bench::{blake3::blake3, fib::fib, merkle, rpo::rpo, sha::sha};
+use miden::{ExecutionProof, FieldExtension, HashFunction, ProofOptions};
+use miden_bench::{
+    blake3::{blake3, blake3_while},
+    falcon::{self, SignedMessage},
+    fib::{fib, fib_while},
+    keccak::keccak,
+    merkle,
+    options::ProofConfig,
+    program::ProgramStats,
+    proof_options,
+    recursive::{self, InnerProof},
+    rpo::{rpo, rpo_while},
+    security,
+    sha::{sha, sha_while},
+    trace::TraceShape,
+};
+use miden_processor::StackOutputs;
 use shared::{
     hash::{rpo::Rpo, HashFn},
-    tree_size_n, Tree,
+    reference, report, tree_size_n, Tree,
 };
 
 #[benchmark]
 fn assert(b: &mut BenchmarkRun) {
-    let (setup, vm) = miden_bench::assert::assert(1, 2);
-    let last_vm_state = vm.last().unwrap().unwrap();
-    let _proof = b.run(setup);
-    b.log("cycles", last_vm_state.clk as usize);
+    run(
+        b,
+        "assert",
+        miden_bench::assert::assert(1, 2, ProofOptions::default()),
+        &[],
+    );
+    for (x, y) in [(1, 2), (1, 1)] {
+        assert_eq!(
+            miden_bench::assert::accepts(x, y),
+            x != y,
+            "program doesn't agree with the reference on {x} != {y}"
+        );
+    }
 }
 
 #[benchmark("multiple assert proof compression")]
 fn multiple_assert_proof_compression(b: &mut BenchmarkRun) {
     let mut proofs = Vec::new();
     for x in 0..10 {
-        let (setup, _) = miden_bench::assert::assert(x, x + 1);
-        let proof = setup();
+        let (setup, _, _) = miden_bench::assert::assert(x, x + 1, ProofOptions::default());
+        let (_, proof) = setup();
         proofs.push(proof);
     }
 
@@ -41,8 +66,10 @@ fn multiple_assert_proof_compression(b: &mut BenchmarkRun) {
 fn multiple_sha256_proof_comperssion(b: &mut BenchmarkRun) {
     let mut proofs = Vec::new();
     for x in 0..10 {
-        let (setup, _) = sha(x + 1);
-        let proof = setup();
+        let bytes = random_bytes(x + 1);
+        let (setup, _, _) = sha(&bytes, ProofOptions::default());
+        let (outputs, proof) = setup();
+        check(&outputs, &miden_bench::sha::expected(&bytes));
         proofs.push(proof);
     }
 
@@ -68,71 +95,190 @@ fn multiple_sha256_proof_comperssion(b: &mut BenchmarkRun) {
     ("100000", 100000),
 ])]
 fn fibonacci(b: &mut BenchmarkRun, p: u32) {
-    let (setup, vm) = fib(p);
-    let last_vm_state = vm.last().unwrap().unwrap();
-    let proof = b.run(setup);
-    let proof = proof.to_bytes();
-    b.log("proof_size_bytes", proof.len());
-    b.log("cycles", last_vm_state.clk as usize);
+    run(
+        b,
+        "Fibonacci",
+        fib(p, ProofOptions::default()),
+        &miden_bench::fib::expected(p),
+    );
 }
 
-// Averages 464.654 cycles per byte
+// Averages 135.682 cycles per byte
 #[benchmark("SHA256", [
     ("1k bytes", 1000),
     ("10k bytes", 10000),
     // ("100k bytes", 100000),
 ])]
 fn sha256(b: &mut BenchmarkRun, p: usize) {
-    let (setup, vm) = sha(p);
-    let last_vm_state = vm.last().unwrap().unwrap();
-    let proof = b.run(setup);
-    b.log("cycles", last_vm_state.clk as usize);
+    report::record(
+        "miden",
+        "SHA256",
+        &b.name,
+        "input",
+        &miden_bench::sha::input(p),
+    );
 
-    let proof = proof.to_bytes();
-    b.log("proof_size_bytes", proof.len());
-    b.log(
-        "compressed_proof_size_bytes",
-        zstd::encode_all(&proof[..], 21).unwrap().len(),
+    let bytes = random_bytes(p);
+    run(
+        b,
+        "SHA256",
+        sha(&bytes, ProofOptions::default()),
+        &miden_bench::sha::expected(&bytes),
     );
 }
 
-// Averages 153.854 cycles per byte
+// Averages 79.267 cycles per byte
 #[benchmark("Blake3", [
     ("1k bytes", 1000),
     ("10k bytes", 10000),
     // ("100k bytes", 100000),
 ])]
 fn blake3_bench(b: &mut BenchmarkRun, p: usize) {
-    let (setup, vm) = blake3(p);
-    let last_vm_state = vm.last().unwrap().unwrap();
-    let proof = b.run(setup);
-    b.log("cycles", last_vm_state.clk as usize);
+    report::record(
+        "miden",
+        "Blake3",
+        &b.name,
+        "input",
+        &miden_bench::blake3::input(p),
+    );
 
-    let proof = &proof.to_bytes();
-    b.log("proof_size_bytes", proof.len());
-    b.log(
-        "compressed_proof_size_bytes",
-        zstd::encode_all(&proof[..], 21).unwrap().len(),
+    let bytes = random_bytes(p);
+    run(
+        b,
+        "Blake3",
+        blake3(&bytes, ProofOptions::default()),
+        &miden_bench::blake3::expected(&bytes),
     );
 }
 
-// Averages 0.869 cycles per byte
+// Averages 0.207 cycles per byte
 #[benchmark("RPO", [
     ("1k bytes", 1000),
     ("10k bytes", 10000),
     // ("100k bytes", 100000),
 ])]
 fn rpo_bench(b: &mut BenchmarkRun, p: usize) {
-    let (setup, vm) = rpo(p);
-    let last_vm_state = vm.last().unwrap().unwrap();
-    let proof = b.run(setup);
-    b.log("cycles", last_vm_state.clk as usize);
+    report::record(
+        "miden",
+        "RPO",
+        &b.name,
+        "input",
+        &miden_bench::rpo::input(p),
+    );
 
-    let proof = &proof.to_bytes();
-    b.log("proof_size_bytes", proof.len());
-    b.log(
-        "compressed_proof_size_bytes",
-        zstd::encode_all(&proof[..], 21).unwrap().len(),
+    let bytes = random_bytes(p);
+    run(
+        b,
+        "RPO",
+        rpo(&bytes, ProofOptions::default()),
+        &miden_bench::rpo::expected(&bytes),
+    );
+}
+
+#[benchmark("Keccak256", [
+    ("1k bytes", 1000),
+    ("10k bytes", 10000),
+])]
+fn keccak256(b: &mut BenchmarkRun, p: usize) {
+    report::record(
+        "miden",
+        "Keccak256",
+        &b.name,
+        "input",
+        &miden_bench::keccak::input(p),
+    );
+
+    let bytes = random_bytes(p);
+    run(
+        b,
+        "Keccak256",
+        keccak(&bytes, ProofOptions::default()),
+        &miden_bench::keccak::expected(&bytes),
+    );
+}
+
+// The while variants take the size as a public input, so one program serves every
+// size, where the repeat variants above are unrolled into a program per size
+#[benchmark("Fibonacci while", [
+    ("1", 1),
+    ("10", 10),
+    ("100", 100),
+    ("1000", 1000),
+    ("10000", 10000),
+    ("100000", 100000),
+])]
+fn fibonacci_while(b: &mut BenchmarkRun, p: u32) {
+    run(
+        b,
+        "Fibonacci while",
+        fib_while(p, ProofOptions::default()),
+        &miden_bench::fib::expected(p),
+    );
+}
+
+#[benchmark("SHA256 while", [
+    ("1k bytes", 1000),
+    ("10k bytes", 10000),
+])]
+fn sha256_while(b: &mut BenchmarkRun, p: usize) {
+    report::record(
+        "miden",
+        "SHA256 while",
+        &b.name,
+        "input",
+        &miden_bench::sha::input(p),
+    );
+
+    let bytes = random_bytes(p);
+    run(
+        b,
+        "SHA256 while",
+        sha_while(&bytes, ProofOptions::default()),
+        &miden_bench::sha::expected(&bytes),
+    );
+}
+
+#[benchmark("Blake3 while", [
+    ("1k bytes", 1000),
+    ("10k bytes", 10000),
+])]
+fn blake3_while_bench(b: &mut BenchmarkRun, p: usize) {
+    report::record(
+        "miden",
+        "Blake3 while",
+        &b.name,
+        "input",
+        &miden_bench::blake3::input(p),
+    );
+
+    let bytes = random_bytes(p);
+    run(
+        b,
+        "Blake3 while",
+        blake3_while(&bytes, ProofOptions::default()),
+        &miden_bench::blake3::expected(&bytes),
+    );
+}
+
+#[benchmark("RPO while", [
+    ("1k bytes", 1000),
+    ("10k bytes", 10000),
+])]
+fn rpo_while_bench(b: &mut BenchmarkRun, p: usize) {
+    report::record(
+        "miden",
+        "RPO while",
+        &b.name,
+        "input",
+        &miden_bench::rpo::input(p),
+    );
+
+    let bytes = random_bytes(p);
+    run(
+        b,
+        "RPO while",
+        rpo_while(&bytes, ProofOptions::default()),
+        &miden_bench::rpo::expected(&bytes),
     );
 }
 
@@ -143,46 +289,309 @@ fn rpo_bench(b: &mut BenchmarkRun, p: usize) {
     ("2^20 + 2^20", (tree_size_n(20), tree_size_n(20))),
 ])]
 fn merkle_tree_merge(b: &mut BenchmarkRun, (tree1, tree2): (Tree<Rpo>, Tree<Rpo>)) {
-    let (prove, iter) = merkle::merge_trees(&tree1, &tree2);
+    run(
+        b,
+        "Merkle Tree Merge",
+        merkle::merge_trees(&tree1, &tree2, ProofOptions::default()),
+        &merkle::merge_trees_expected(&tree1, &tree2),
+    );
+}
 
-    let proof = b.run(prove);
-    let proof_bytes = proof.to_bytes();
-    let proof_bytes_zstd = zstd::encode_all(&*proof_bytes, 21).unwrap();
+// Batches of k native Merkle store instructions on random leaves
+#[benchmark("Merkle Store Get", [
+    ("2^10, k = 1", (10, 1)),
+    ("2^10, k = 16", (10, 16)),
+    ("2^15, k = 16", (15, 16)),
+    ("2^20, k = 1", (20, 1)),
+    ("2^20, k = 16", (20, 16)),
+])]
+fn merkle_store_get(b: &mut BenchmarkRun, (n, k): (usize, usize)) {
+    let tree = tree_size_n(n);
+    let indices = random_leaves(n, k);
+    run(
+        b,
+        "Merkle Store Get",
+        merkle::mtree_get(&tree, &indices, ProofOptions::default()),
+        &merkle::root_expected(&tree),
+    );
+}
 
-    b.log("proof_size_bytes", proof_bytes.len());
-    b.log("compressed_proof_size_bytes", proof_bytes_zstd.len());
-    let last_vm_state = iter.last().unwrap().unwrap();
+#[benchmark("Merkle Store Set", [
+    ("2^10, k = 1", (10, 1)),
+    ("2^10, k = 16", (10, 16)),
+    ("2^15, k = 16", (15, 16)),
+    ("2^20, k = 1", (20, 1)),
+    ("2^20, k = 16", (20, 16)),
+])]
+fn merkle_store_set(b: &mut BenchmarkRun, (n, k): (usize, usize)) {
+    let tree = tree_size_n(n);
+    let updates = random_leaves(n, k)
+        .into_iter()
+        .map(|index| (index, Rpo::random()))
+        .collect::<Vec<_>>();
+    run(
+        b,
+        "Merkle Store Set",
+        merkle::mtree_set(&tree, &updates, ProofOptions::default()),
+        &merkle::mtree_set_expected(&tree, &updates),
+    );
+}
 
-    b.log("cycles", last_vm_state.clk as usize);
+#[benchmark("Merkle Store Verify", [
+    ("2^10, k = 1", (10, 1)),
+    ("2^10, k = 16", (10, 16)),
+    ("2^15, k = 16", (15, 16)),
+    ("2^20, k = 1", (20, 1)),
+    ("2^20, k = 16", (20, 16)),
+])]
+fn merkle_store_verify(b: &mut BenchmarkRun, (n, k): (usize, usize)) {
+    let tree = tree_size_n(n);
+    let indices = random_leaves(n, k);
+    run(
+        b,
+        "Merkle Store Verify",
+        merkle::mtree_verify(&tree, &indices, ProofOptions::default()),
+        &merkle::root_expected(&tree),
+    );
+}
+
+/// Positions of `k` random leaves of a tree with 2^n leaves
+fn random_leaves(n: usize, k: usize) -> Vec<u64> {
+    core::iter::repeat_with(|| shared::fastrand::u64(..1 << n))
+        .take(k)
+        .collect()
 }
 
 #[benchmark("Merkle Membership")]
 fn merkle_membership(b: &mut BenchmarkRun) {
-    let vec = core::iter::from_fn(|| Some(Rpo::random()))
-        .take(10)
-        .collect();
-    let (prove, iter) = merkle::membership(vec, Rpo::random());
+    membership(b, "Merkle Membership", 10);
+}
 
-    let proof = b.run(prove);
-    let proof_bytes = proof.to_bytes();
-    let proof_bytes_zstd = zstd::encode_all(&*proof_bytes, 21).unwrap();
+#[benchmark("Merkle Membership depth", [
+    ("8", 8),
+    ("16", 16),
+    ("20", 20),
+    ("32", 32),
+    ("64", 64),
+])]
+fn merkle_membership_depth(b: &mut BenchmarkRun, depth: usize) {
+    membership(b, "Merkle Membership depth", depth);
+}
 
-    b.log("proof_size_bytes", proof_bytes.len());
-    b.log("compressed_proof_size_bytes", proof_bytes_zstd.len());
-    let last_vm_state = iter.last().unwrap().unwrap();
-
-    b.log("cycles", last_vm_state.clk as usize);
-}
-
-benchy::main!(
-    "miden",
-    assert,
-    multiple_assert_proof_compression,
-    multiple_sha256_proof_comperssion,
-    fibonacci,
-    sha256,
-    blake3_bench,
-    rpo_bench,
-    merkle_tree_merge,
-    merkle_membership,
-);
+fn membership(b: &mut BenchmarkRun, benchmark: &str, depth: usize) {
+    let path = core::iter::from_fn(|| Some(Rpo::random()))
+        .take(depth)
+        .collect::<Vec<_>>();
+    let leaf = Rpo::random();
+    let index =
+        shared::fastrand::u64(..) & 1u64.checked_shl(depth as u32).map_or(u64::MAX, |m| m - 1);
+    let expected = merkle::membership_expected(&path, leaf, index);
+    let root = reference::merkle_path::<Rpo>(leaf, index, &path);
+    run(
+        b,
+        benchmark,
+        merkle::membership(path, leaf, index, root, ProofOptions::default()),
+        &expected,
+    );
+}
+
+#[benchmark("Falcon512 Verify", [
+    ("1 signature", 1),
+    ("2 signatures", 2),
+    ("4 signatures", 4),
+    ("8 signatures", 8),
+])]
+fn falcon512_verify(b: &mut BenchmarkRun, n: usize) {
+    let signed = core::iter::repeat_with(SignedMessage::random)
+        .take(n)
+        .collect::<Vec<_>>();
+    let job = falcon::verify(&signed, ProofOptions::default());
+    let cycles = job.1.main_len;
+    let proof = run(b, "Falcon512 Verify", job, &falcon::expected(&signed));
+
+    b.log("cycles_per_signature", cycles / n);
+    b.log(
+        "proving_time_per_signature_us",
+        b.time.as_micros() as usize / n,
+    );
+    b.log("proof_size_bytes_per_signature", proof.len() / n);
+}
+
+// The inner proof uses the only options the stdlib verifier supports, and is
+// made before the run, so only verifying it is timed
+#[benchmark("Recursive Verify")]
+fn recursive_verify(b: &mut BenchmarkRun) {
+    let inner = InnerProof::assert(1, 2);
+    run(
+        b,
+        "Recursive Verify",
+        recursive::verify(&inner, ProofOptions::default()),
+        &[],
+    );
+
+    b.log("inner_cycles", inner.shape.main_len);
+    b.log("inner_trace_len", inner.shape.padded_len);
+    b.log(
+        "inner_proving_time_us",
+        inner.proving_time.as_micros() as usize,
+    );
+    b.log("inner_proof_size_bytes", inner.proof.to_bytes().len());
+}
+
+// risc-zero's security level is fixed at ~100 bits, so these are the miden
+// numbers to compare against it
+#[benchmark("Fibonacci security level", [
+    ("96 bits", 96),
+    ("100 bits", 100),
+    ("128 bits", 128),
+])]
+fn fibonacci_security_level(b: &mut BenchmarkRun, bits: u32) {
+    run(
+        b,
+        "Fibonacci security level",
+        fib(1000, proof_options(bits)),
+        &miden_bench::fib::expected(1000),
+    );
+}
+
+#[benchmark("SHA256 security level", [
+    ("96 bits", 96),
+    ("100 bits", 100),
+    ("128 bits", 128),
+])]
+fn sha256_security_level(b: &mut BenchmarkRun, bits: u32) {
+    let bytes = random_bytes(1000);
+    run(
+        b,
+        "SHA256 security level",
+        sha(&bytes, proof_options(bits)),
+        &miden_bench::sha::expected(&bytes),
+    );
+}
+
+// Each case changes a single parameter of the 96 bit preset, which also changes
+// the security level (recorded with the results)
+#[benchmark("Proof options", [
+    ("96 bit preset", ProofConfig::with_96_bit_security()),
+    ("128 bit preset", ProofConfig::with_128_bit_security()),
+    ("blowup 16", ProofConfig::with_96_bit_security().with_blowup_factor(16)),
+    ("blowup 32", ProofConfig::with_96_bit_security().with_blowup_factor(32)),
+    ("grinding 0", ProofConfig::with_96_bit_security().with_grinding_factor(0)),
+    ("grinding 20", ProofConfig::with_96_bit_security().with_grinding_factor(20)),
+    ("FRI folding 4", ProofConfig::with_96_bit_security().with_fri_folding_factor(4)),
+    ("FRI folding 16", ProofConfig::with_96_bit_security().with_fri_folding_factor(16)),
+    ("FRI remainder 63", ProofConfig::with_96_bit_security().with_fri_max_remainder_size(63)),
+    ("FRI remainder 127", ProofConfig::with_96_bit_security().with_fri_max_remainder_size(127)),
+    ("cubic extension", ProofConfig::with_96_bit_security().with_field_extension(FieldExtension::Cubic)),
+    ("Blake3_256", ProofConfig::with_96_bit_security().with_hash_fn(HashFunction::Blake3_256)),
+    ("RPO", ProofConfig::with_96_bit_security().with_hash_fn(HashFunction::Rpo256)),
+])]
+fn proof_options_sweep(b: &mut BenchmarkRun, config: ProofConfig) {
+    run(
+        b,
+        "Proof options",
+        fib(10000, config.into()),
+        &miden_bench::fib::expected(10000),
+    );
+}
+
+/// Proves with a miden helper's prover, checks that the outputs start with
+/// `expected`, and logs the proof along with the trace and program the helper
+/// measured. Returns the serialized proof.
+fn run(
+    b: &mut BenchmarkRun,
+    benchmark: &str,
+    (prove, shape, stats): (
+        impl Fn() -> (StackOutputs, ExecutionProof),
+        TraceShape,
+        ProgramStats,
+    ),
+    expected: &[u64],
+) -> Vec<u8> {
+    let (outputs, proof) = b.run(prove);
+    check(&outputs, expected);
+    report::record("miden", benchmark, &b.name, "security", &security(&proof));
+    log_trace(b, &shape);
+    log_program(b, benchmark, &stats);
+
+    let proof = proof.to_bytes();
+    b.log("proof_size_bytes", proof.len());
+    b.log(
+        "compressed_proof_size_bytes",
+        zstd::encode_all(&proof[..], 21).unwrap().len(),
+    );
+    proof
+}
+
+fn log_program(b: &mut BenchmarkRun, benchmark: &str, stats: &ProgramStats) {
+    b.log("compile_time_us", stats.compile_time.as_micros() as usize);
+    b.log("program_cached", stats.cached as usize);
+    b.log("mast_nodes", stats.mast_nodes);
+    b.log("span_blocks", stats.span_blocks);
+    b.log("program_operations", stats.operations);
+    if let Some(size) = stats.serialized_size {
+        b.log("program_size_bytes", size);
+    }
+    report::record(
+        "miden",
+        benchmark,
+        &b.name,
+        "program_hash",
+        &stats.hash_hex(),
+    );
+}
+
+fn log_trace(b: &mut BenchmarkRun, shape: &TraceShape) {
+    b.log("cycles", shape.main_len);
+    b.log("padded_trace_len", shape.padded_len);
+    b.log("range_checker_rows", shape.range_checker_len);
+    b.log("chiplets_rows", shape.chiplets_len);
+    b.log("hasher_rows", shape.hasher_len);
+    b.log("bitwise_rows", shape.bitwise_len);
+    b.log("memory_rows", shape.memory_len);
+}
+
+fn random_bytes(n: usize) -> Vec<u8> {
+    core::iter::repeat_with(|| shared::fastrand::u8(..))
+        .take(n)
+        .collect()
+}
+
+fn check(outputs: &StackOutputs, expected: &[u64]) {
+    assert_eq!(
+        &outputs.stack()[..expected.len()],
+        expected,
+        "program output doesn't match the reference"
+    );
+}
+
+fn main() {
+    report::reset("miden");
+    benchy::main!(
+        "miden",
+        assert,
+        multiple_assert_proof_compression,
+        multiple_sha256_proof_comperssion,
+        fibonacci,
+        sha256,
+        blake3_bench,
+        rpo_bench,
+        keccak256,
+        fibonacci_while,
+        sha256_while,
+        blake3_while_bench,
+        rpo_while_bench,
+        merkle_tree_merge,
+        merkle_store_get,
+        merkle_store_set,
+        merkle_store_verify,
+        merkle_membership,
+        merkle_membership_depth,
+        falcon512_verify,
+        recursive_verify,
+        fibonacci_security_level,
+        sha256_security_level,
+        proof_options_sweep,
+    );
+    main();
+}
@@ -0,0 +1,29 @@
+//! Passes the miden-vm version from Cargo.lock to the program cache as
+//! `MIDEN_VM_VERSION`, so that bumping the dependency invalidates the cache.
+
+use std::{env, fs, path::PathBuf};
+
+fn main() {
+    let lock = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock.display());
+
+    let lock = fs::read_to_string(&lock).unwrap();
+    let package = lock
+        .split("[[package]]")
+        .find(|package| package.lines().any(|line| line == r#"name = "miden-vm""#))
+        .expect("miden-vm isn't in Cargo.lock");
+    let field = |key: &str| {
+        package
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(" = "))
+            .map(|value| value.trim_matches('"'))
+            .unwrap_or_default()
+    };
+
+    // A git dependency's source includes the commit, which the version alone doesn't
+    println!(
+        "cargo:rustc-env=MIDEN_VM_VERSION={} {}",
+        field("version"),
+        field("source")
+    );
+}
@@ -1,41 +1,60 @@
-use miden::{Assembler, ProofOptions};
-use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, VmStateIterator};
+use miden::ProofOptions;
+use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, StackOutputs};
 use miden_prover::ExecutionProof;
 
-pub fn assert(a: u32, b: u32) -> (impl Fn() -> ExecutionProof, VmStateIterator) {
-    let code = r#"
-        begin
-            adv_push.2
-            u32checked_neq
-            assert
-        end
-    "#
-    .to_string();
+use crate::{
+    program::{self, ProgramStats},
+    trace::TraceShape,
+};
 
-    let assembler = Assembler::default()
-        .with_library(&miden_stdlib::StdLibrary::default())
-        .unwrap();
-    let program = assembler.compile(code).unwrap();
-    let advice_provider = MemAdviceProvider::from(
-        AdviceInputs::default()
-            .with_stack_values(vec![a as u64, b as u64])
-            .unwrap(),
-    );
-    let vm: miden_processor::VmStateIterator =
-        miden_processor::execute_iter(&program, StackInputs::default(), advice_provider.clone());
+/// Reads two numbers from the advice stack and asserts they differ
+pub(crate) const CODE: &str = r#"
+    begin
+        adv_push.2
+        u32checked_neq
+        assert
+    end
+"#;
+
+pub fn assert(
+    a: u32,
+    b: u32,
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let (program, stats) = program::compile(CODE);
+    let advice_provider = advice_provider(a, b);
+    let shape = TraceShape::measure(&program, StackInputs::default(), advice_provider.clone());
 
     (
         move || {
-            let (_stack, proof) = miden::prove(
+            miden::prove(
                 &program,
                 StackInputs::default(),
                 advice_provider.clone(),
-                ProofOptions::default(),
+                options.clone(),
             )
-            .unwrap();
-
-            proof
+            .unwrap()
         },
-        vm,
+        shape,
+        stats,
+    )
+}
+
+/// Whether the program runs to completion, which is its only output, for `a` and `b`.
+/// It's executed without proving, so it's cheap enough to check with every run.
+pub fn accepts(a: u32, b: u32) -> bool {
+    let (program, _) = program::compile(CODE);
+    miden_processor::execute(&program, StackInputs::default(), advice_provider(a, b)).is_ok()
+}
+
+fn advice_provider(a: u32, b: u32) -> MemAdviceProvider {
+    MemAdviceProvider::from(
+        AdviceInputs::default()
+            .with_stack_values(vec![a as u64, b as u64])
+            .unwrap(),
     )
 }
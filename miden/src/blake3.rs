@@ -1,47 +1,386 @@
-use miden::{Assembler, ProofOptions};
-use miden_processor::{MemAdviceProvider, StackInputs, VmStateIterator};
+use std::fmt::Write;
+
+use miden::{Program, ProofOptions};
+use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, StackOutputs};
 use miden_prover::ExecutionProof;
+use shared::{
+    input::{blake3_blocks, Hash, HashInput, Visibility},
+    reference,
+};
+
+use crate::{
+    message::{advice_stack, push_block, MessageWord},
+    program::{self, ProgramStats},
+    trace::TraceShape,
+};
+
+const CHUNK_LEN: usize = 1024;
+const BLOCK_LEN: usize = 64;
+
+// Domain separation flags
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+/// The message is private and read from the advice stack. Each 1024 byte chunk is
+/// compressed a block at a time, then the chunks are merged in a binary tree.
+pub fn input(n_bytes: usize) -> HashInput {
+    HashInput::new(Hash::Blake3, n_bytes, Visibility::Private)
+        .with_compressions(blake3_blocks(n_bytes))
+}
+
+/// The digest words at the top of the stack after `blake3(bytes)`
+pub fn expected(bytes: &[u8]) -> Vec<u64> {
+    reference::blake3(bytes)
+        .chunks(4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()).into())
+        .collect()
+}
+
+/// The message as little endian words, with the last one zero padded
+fn words(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks(4)
+        .map(|chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_le_bytes(word)
+        })
+        .collect()
+}
+
+/// The 16 words of the (zero padded) message block starting at byte `start`
+fn block(start: usize, n_bytes: usize) -> [MessageWord; 16] {
+    core::array::from_fn(|i| match start + i * 4 {
+        word_start if word_start + 4 <= n_bytes => MessageWord::Advice,
+        word_start if word_start < n_bytes => MessageWord::Partial {
+            // Little endian, so the message bytes are the low ones
+            mask: u32::MAX << (8 * (n_bytes - word_start)),
+            pad: 0,
+        },
+        _ => MessageWord::Constant(0),
+    })
+}
+
+/// Pops the chaining values of the two children of a node, which are on top of the
+/// stack with the right child on top, and pushes the node's chaining value.
+fn push_parent(code: &mut String, flags: u32) {
+    writeln!(
+        code,
+        "swapdw exec.init_chaining_value push.{flags}.{BLOCK_LEN}.0 exec.compress_block
+        padw mem_loadw.1 padw mem_loadw.0"
+    )
+    .unwrap();
+}
+
+/// The program hashing `bytes`, and the advice provider with the message
+fn hash_program(bytes: &[u8]) -> (Program, ProgramStats, MemAdviceProvider) {
+    // See https://github.com/BLAKE3-team/BLAKE3/blob/da4c792/reference_impl/reference_impl.rs
+    let n_bytes = bytes.len();
+    let n_chunks = usize::max(1, n_bytes.div_ceil(CHUNK_LEN));
+
+    let mut blocks = Vec::new();
+    let mut hash_chunks = String::new();
+    // Chaining values of completed subtrees are kept on the stack
+    let mut subtrees = 0;
+    for chunk in 0..n_chunks {
+        let chunk_start = chunk * CHUNK_LEN;
+        let chunk_len = usize::min(CHUNK_LEN, n_bytes - chunk_start);
+        let n_blocks = usize::max(1, chunk_len.div_ceil(BLOCK_LEN));
+
+        hash_chunks.push_str("exec.init_chaining_value\n");
+        for i in 0..n_blocks {
+            let block_start = chunk_start + i * BLOCK_LEN;
+            let block_len = usize::min(BLOCK_LEN, n_bytes - block_start);
+            let mut flags = 0;
+            if i == 0 {
+                flags |= CHUNK_START;
+            }
+            if i == n_blocks - 1 {
+                flags |= CHUNK_END;
+                if n_chunks == 1 {
+                    flags |= ROOT;
+                }
+            }
+
+            let block = block(block_start, n_bytes);
+            writeln!(
+                hash_chunks,
+                "{}push.{flags}.{block_len}.{chunk} exec.compress_block",
+                push_block(&block)
+            )
+            .unwrap();
+            blocks.push(block);
+        }
+        hash_chunks.push_str("padw mem_loadw.1 padw mem_loadw.0\n");
+        subtrees += 1;
+
+        // Merge every pair of complete subtrees, except after the last chunk where the
+        // root needs its own flag
+        if chunk < n_chunks - 1 {
+            let mut total_chunks = chunk + 1;
+            while total_chunks % 2 == 0 {
+                push_parent(&mut hash_chunks, PARENT);
+                subtrees -= 1;
+                total_chunks /= 2;
+            }
+        }
+    }
+    while subtrees > 1 {
+        subtrees -= 1;
+        let flags = if subtrees == 1 { PARENT | ROOT } else { PARENT };
+        push_parent(&mut hash_chunks, flags);
+    }
 
-pub fn blake3(n_bytes: usize) -> (impl Fn() -> ExecutionProof, VmStateIterator) {
-    // Input: 32-bit value per element, 16 elements per hash. 8 elements will be spent on the previous hash.
-    // Output: 32-byte digest stored in the first 8 elements of the stack.
-    let blake3_ops = f64::ceil(n_bytes as f64 / 4. / 8.);
     let code = format!(
         r#"
-        use.std::crypto::hashes::blake3
+        {procedures}
 
         begin
-            push.1.2.3.4.5.6.7.8
-            repeat.{blake3_ops}
-                push.9.10.11.12.13.14.15.16
-                exec.blake3::hash_2to1
-            end
+            {hash_chunks}
         end
-    "#
+    "#,
+        procedures = include_str!("./asm/blake3.masm"),
     );
 
-    let assembler = Assembler::default()
-        .with_library(&miden_stdlib::StdLibrary::default())
-        .unwrap();
-    let program = assembler.compile(code).unwrap();
-    let vm: miden_processor::VmStateIterator = miden_processor::execute_iter(
-        &program,
-        StackInputs::default(),
-        MemAdviceProvider::default(),
+    let (program, stats) = program::compile(&code);
+
+    let advice_provider = MemAdviceProvider::from(
+        AdviceInputs::default()
+            .with_stack_values(advice_stack(&blocks, &words(bytes)))
+            .unwrap(),
     );
+    (program, stats, advice_provider)
+}
+
+pub fn blake3(
+    bytes: &[u8],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let (program, stats, advice_provider) = hash_program(bytes);
+    let shape = TraceShape::measure(&program, StackInputs::default(), advice_provider.clone());
 
     (
         move || {
-            let (_stack, proof) = miden::prove(
+            miden::prove(
                 &program,
                 StackInputs::default(),
-                MemAdviceProvider::default(),
-                ProofOptions::default(),
+                advice_provider.clone(),
+                options.clone(),
             )
-            .unwrap();
+            .unwrap()
+        },
+        shape,
+        stats,
+    )
+}
+
+/// The program `blake3_while` proves, its inputs, and the advice provider with the
+/// message
+fn while_program(bytes: &[u8]) -> (Program, ProgramStats, StackInputs, MemAdviceProvider) {
+    // Counters are kept in memory, after the chaining value in 0 and 1, as the stack
+    // holds the chaining values of the complete subtrees:
+    // 2: chunks hashed, 3: total chunks, 4: flags of the last block of a chunk,
+    // 5: subtrees on the stack, 6: complete subtrees to merge, 7: parent flags,
+    // 8: message length, 9: blocks in this chunk, 10: length of its last block,
+    // 11: offset of the word being read, 12: blocks of this chunk hashed
+    let code = format!(
+        r#"
+        {procedures}
+
+        # Pushes the word at offset mem[11] from the advice stack, checks that any bytes
+        # past the end of the message are zero, and moves the offset to the previous word
+        proc.tail_word
+            adv_push.1 u32assert
+            # => [word, ...]
+            mem_load.11 mem_load.8 u32checked_gte
+            if.true
+                dup assertz
+            else
+                mem_load.11 add.4 mem_load.8 u32checked_gt
+                if.true
+                    # Little endian, so the message bytes are the low ones
+                    dup
+                    push.0xffffffff mem_load.8 mem_load.11 sub mul.8 u32checked_shl
+                    u32checked_and assertz
+                end
+            end
+            mem_load.11 sub.4 mem_store.11
+        end
+
+        proc.hash_chunk
+            exec.init_chaining_value
+
+            mem_load.8 mem_load.2 mul.{CHUNK_LEN} sub push.{CHUNK_LEN} u32checked_min
+            # => [chunk_len, ...]
+            # An empty chunk still has one (empty) block
+            dup add.{last_byte} u32checked_div.{BLOCK_LEN}
+            dup.1 eq.0 add
+            dup mem_store.9
+            sub.1 mul.{BLOCK_LEN} sub mem_store.10
+
+            push.0 mem_store.12
+            mem_load.9 neq.1
+            while.true
+                {push_block}
+                mem_load.12 eq.0 mul.{CHUNK_START} push.{BLOCK_LEN} mem_load.2 exec.compress_block
+                mem_load.12 add.1 dup mem_store.12
+                mem_load.9 sub.1 neq
+            end
+
+            mem_load.2 mul.{CHUNK_LEN} mem_load.9 sub.1 mul.{BLOCK_LEN} add add.60
+            mem_store.11
+            repeat.16 exec.tail_word end
+            mem_load.9 eq.1 mul.{CHUNK_START} mem_load.4 add
+            mem_load.10 mem_load.2 exec.compress_block
+            padw mem_loadw.1 padw mem_loadw.0
+        end
+
+        proc.merge
+            swapdw exec.init_chaining_value mem_load.7 push.{BLOCK_LEN}.0 exec.compress_block
+            padw mem_loadw.1 padw mem_loadw.0
+        end
+
+        begin
+            # => [n_bytes, ...]
+            dup mem_store.8
+            # An empty message is one empty chunk
+            dup add.{last_chunk_byte} u32checked_div.{CHUNK_LEN} swap eq.0 add
+            dup mem_store.3
+            # A single chunk is the root
+            eq.1 mul.{ROOT} add.{CHUNK_END} mem_store.4
+            push.{PARENT} mem_store.7
+
+            push.1
+            while.true
+                exec.hash_chunk
+                mem_load.5 add.1 mem_store.5
+                mem_load.2 add.1 dup mem_store.2
+                # => [chunks, CV, ...]
+
+                # Merge every pair of complete subtrees, except after the last chunk
+                # where the root needs its own flag
+                dup mem_load.3 neq
+                if.true
+                    dup mem_store.6
+                    push.1 u32checked_and eq.0
+                    while.true
+                        exec.merge
+                        mem_load.5 sub.1 mem_store.5
+                        mem_load.6 u32checked_div.2 dup mem_store.6
+                        push.1 u32checked_and eq.0
+                    end
+                else
+                    drop
+                end
 
-            proof
+                mem_load.2 mem_load.3 neq
+            end
+
+            mem_load.5 neq.1
+            while.true
+                mem_load.5 sub.1 dup mem_store.5
+                eq.1 mul.{ROOT} add.{PARENT} mem_store.7
+                exec.merge
+                mem_load.5 neq.1
+            end
+        end
+    "#,
+        procedures = include_str!("./asm/blake3.masm"),
+        push_block = push_block(&[MessageWord::Advice; 16]),
+        last_byte = BLOCK_LEN - 1,
+        last_chunk_byte = CHUNK_LEN - 1,
+    );
+
+    let (program, stats) = program::compile(&code);
+
+    // Every block is read from the advice stack in full, with the message zero padded
+    let n_blocks = usize::max(1, bytes.len().div_ceil(BLOCK_LEN));
+    let blocks = vec![[MessageWord::Advice; 16]; n_blocks];
+    let mut padded = bytes.to_vec();
+    padded.resize(n_blocks * BLOCK_LEN, 0);
+
+    let stack_inputs = StackInputs::try_from_values([bytes.len() as u64]).unwrap();
+    let advice_provider = MemAdviceProvider::from(
+        AdviceInputs::default()
+            .with_stack_values(advice_stack(&blocks, &words(&padded)))
+            .unwrap(),
+    );
+    (program, stats, stack_inputs, advice_provider)
+}
+
+/// Like `blake3`, but the message length is a public input and the chunks are hashed
+/// and merged in loops at runtime, so the same program hashes every length. The last
+/// block of each chunk is read a word at a time, and each word is checked against
+/// where the message ends.
+pub fn blake3_while(
+    bytes: &[u8],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let (program, stats, stack_inputs, advice_provider) = while_program(bytes);
+    let shape = TraceShape::measure(&program, stack_inputs.clone(), advice_provider.clone());
+
+    (
+        move || {
+            miden::prove(
+                &program,
+                stack_inputs.clone(),
+                advice_provider.clone(),
+                options.clone(),
+            )
+            .unwrap()
         },
-        vm,
+        shape,
+        stats,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_match_the_reference() {
+        for n_bytes in [0, 1, 3, 63, 64, 65, 1023, 1024, 1025, 2048, 3073] {
+            let bytes = core::iter::repeat_with(|| shared::fastrand::u8(..))
+                .take(n_bytes)
+                .collect::<Vec<_>>();
+            let (program, _, advice_provider) = hash_program(&bytes);
+            let trace = miden_processor::execute(&program, StackInputs::default(), advice_provider)
+                .unwrap();
+
+            let expected = expected(&bytes);
+            assert_eq!(
+                &trace.stack_outputs().stack()[..expected.len()],
+                expected,
+                "digest of {n_bytes} bytes"
+            );
+        }
+    }
+    #[test]
+    fn while_digests_match_the_reference() {
+        for n_bytes in [0, 1, 3, 63, 64, 65, 1023, 1024, 1025, 2048, 3073] {
+            let bytes = core::iter::repeat_with(|| shared::fastrand::u8(..))
+                .take(n_bytes)
+                .collect::<Vec<_>>();
+            let (program, _, stack_inputs, advice_provider) = while_program(&bytes);
+            let trace = miden_processor::execute(&program, stack_inputs, advice_provider).unwrap();
+
+            let expected = expected(&bytes);
+            assert_eq!(
+                &trace.stack_outputs().stack()[..expected.len()],
+                expected,
+                "digest of {n_bytes} bytes"
+            );
+        }
+    }
+}
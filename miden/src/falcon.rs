@@ -0,0 +1,160 @@
+use miden::Program;
+use miden_crypto::{
+    hash::rpo::{Rpo256, RpoDigest},
+    Felt, Word,
+};
+use miden_crypto_dsa::dsa::rpo_falcon512::{KeyPair, Polynomial, Signature};
+use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, StackOutputs};
+use miden_prover::{ExecutionProof, ProofOptions};
+
+use crate::{
+    program::{self, ProgramStats},
+    trace::TraceShape,
+    word_on_stack,
+};
+
+/// A random message digest, signed on the host with a fresh key pair
+pub struct SignedMessage {
+    /// The RPO hash of the public key polynomial
+    pub public_key: Word,
+    pub message: Word,
+    pub signature: Signature,
+}
+
+impl SignedMessage {
+    pub fn random() -> Self {
+        let key_pair = KeyPair::new().unwrap();
+        let message = core::array::from_fn(|_| Felt::new(shared::fastrand::u64(..)));
+        let signature = key_pair.sign(message).unwrap();
+        assert!(signature.verify(message, key_pair.public_key().into()));
+
+        Self {
+            public_key: key_pair.public_key().into(),
+            message,
+            signature,
+        }
+    }
+
+    /// The nonce, public key h, signature s2 and the product h * s2 in Z[x], which the
+    /// VM uses to compute s1 = c - h * s2. Nothing else binds the product to h and s2
+    /// than the VM evaluating all three at a random point drawn from their hash, so
+    /// verification is only as sound as that check.
+    fn signature_advice(&self) -> impl Iterator<Item = Felt> {
+        let h = self.signature.pub_key_poly();
+        let s2 = self.signature.sig_poly();
+        let product = Polynomial::mul_modulo_p(&h, &s2);
+
+        self.signature
+            .nonce()
+            .into_iter()
+            .chain(h.to_elements())
+            .chain(s2.to_elements())
+            .chain(product.map(Felt::new))
+    }
+}
+
+/// Hash of every (public key, message) pair `verify(signed)` checked a signature of,
+/// which it leaves at the top of the stack
+pub fn expected(signed: &[SignedMessage]) -> Vec<u64> {
+    let commitment = signed
+        .iter()
+        .fold(RpoDigest::default(), |commitment, signed| {
+            let pair = Rpo256::merge(&[signed.public_key.into(), signed.message.into()]);
+            Rpo256::merge(&[commitment, pair])
+        });
+
+    word_on_stack(*commitment)
+}
+
+/// The public key, message and signature advice of each of `signed`, with `tamper`
+/// applied to the advice of each signature
+fn advice(signed: &[SignedMessage], tamper: impl Fn(&mut Vec<Felt>)) -> MemAdviceProvider {
+    let mut advice = AdviceInputs::default();
+    for signed in signed {
+        let mut signature = signed.signature_advice().collect();
+        tamper(&mut signature);
+        advice.extend_stack(signed.public_key);
+        advice.extend_stack(signed.message);
+        advice.extend_stack(signature);
+    }
+    MemAdviceProvider::from(advice)
+}
+
+/// The program verifying `n` signatures
+fn verify_program(n: usize) -> (Program, ProgramStats) {
+    let code = format!(
+        r#"
+        {procedures}
+
+        begin
+            padw
+            repeat.{n}
+                padw adv_loadw padw adv_loadw
+                # => [MSG, PK, COMMITMENT, ...]
+                dupw.1 dupw.1 hmerge
+                movupw.3 swapw hmerge
+                movdnw.2 swapw
+                # => [PK, MSG, COMMITMENT, ...]
+                exec.verify
+            end
+        end
+    "#,
+        procedures = include_str!("./asm/rpo_falcon512.masm"),
+        n = n,
+    );
+
+    program::compile(&code)
+}
+
+/// Verifies a Falcon signature for each of `signed`, whose public keys and messages
+/// are read from the advice stack along with the signatures and hashed into a
+/// commitment, as there are too many to be stack inputs.
+pub fn verify(
+    signed: &[SignedMessage],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let (program, stats) = verify_program(signed.len());
+    let advice = advice(signed, |_| {});
+
+    let shape = TraceShape::measure(&program, StackInputs::default(), advice.clone());
+
+    let prove = move || {
+        miden_prover::prove(
+            &program,
+            StackInputs::default(),
+            advice.clone(),
+            options.clone(),
+        )
+        .unwrap()
+    };
+
+    (prove, shape, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepts(signed: &SignedMessage, tamper: impl Fn(&mut Vec<Felt>)) -> bool {
+        let (program, _) = verify_program(1);
+        let advice = advice(core::slice::from_ref(signed), tamper);
+        miden_processor::execute(&program, StackInputs::default(), advice).is_ok()
+    }
+
+    #[test]
+    fn tampered_signatures_are_rejected() {
+        let signed = SignedMessage::random();
+        assert!(accepts(&signed, |_| {}));
+
+        // The last coefficient of the claimed h * s2, which the host supplies
+        assert!(!accepts(&signed, |signature| {
+            *signature.last_mut().unwrap() += Felt::new(1)
+        }));
+        // The first coefficient of s2, after the nonce and h
+        assert!(!accepts(&signed, |signature| signature[8 + 512] += Felt::new(1)));
+    }
+}
@@ -1,8 +1,27 @@
-use miden::{Assembler, ProofOptions};
-use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, VmStateIterator};
+use miden::ProofOptions;
+use miden_crypto::{Felt, FieldElement, StarkField};
+use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, StackOutputs};
 use miden_prover::ExecutionProof;
+use shared::reference;
 
-pub fn fib(n: u32) -> (impl Fn() -> ExecutionProof, VmStateIterator) {
+use crate::{
+    program::{self, ProgramStats},
+    trace::TraceShape,
+};
+
+/// The top of the stack after `fib(n)`
+pub fn expected(n: u32) -> Vec<u64> {
+    vec![reference::fib(n as usize, Felt::ZERO, Felt::ONE).as_int()]
+}
+
+pub fn fib(
+    n: u32,
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
     let code = format!(
         r#"
         begin
@@ -15,30 +34,73 @@ pub fn fib(n: u32) -> (impl Fn() -> ExecutionProof, VmStateIterator) {
     "#
     );
 
-    let assembler = Assembler::default()
-        .with_library(&miden_stdlib::StdLibrary::default())
-        .unwrap();
-    let program = assembler.compile(code).unwrap();
+    let (program, stats) = program::compile(&code);
     let advice_provider = MemAdviceProvider::from(
         AdviceInputs::default()
             .with_stack_values(vec![n as u64])
             .unwrap(),
     );
-    let vm: miden_processor::VmStateIterator =
-        miden_processor::execute_iter(&program, StackInputs::default(), advice_provider.clone());
+    let shape = TraceShape::measure(&program, StackInputs::default(), advice_provider.clone());
 
     (
         move || {
-            let (_stack, proof) = miden::prove(
+            miden::prove(
                 &program,
                 StackInputs::default(),
                 advice_provider.clone(),
-                ProofOptions::default(),
+                options.clone(),
             )
-            .unwrap();
+            .unwrap()
+        },
+        shape,
+        stats,
+    )
+}
+
+/// Like `fib`, but `n` is a public input and the loop runs at runtime, so the same
+/// program computes every `n`.
+pub fn fib_while(
+    n: u32,
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let code = r#"
+        begin
+            push.0
+            push.1
+            movup.2
+            dup
+            neq.0
+            while.true
+                sub.1
+                movdn.2
+                swap dup.1 add
+                movup.2
+                dup
+                neq.0
+            end
+            drop
+        end
+    "#;
+
+    let (program, stats) = program::compile(code);
+    let stack_inputs = StackInputs::try_from_values([n as u64]).unwrap();
+    let shape = TraceShape::measure(&program, stack_inputs.clone(), MemAdviceProvider::default());
 
-            proof
+    (
+        move || {
+            miden::prove(
+                &program,
+                stack_inputs.clone(),
+                MemAdviceProvider::default(),
+                options.clone(),
+            )
+            .unwrap()
         },
-        vm,
+        shape,
+        stats,
     )
 }
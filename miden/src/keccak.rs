@@ -0,0 +1,198 @@
+use std::fmt::Write;
+
+use miden::{Program, ProofOptions};
+use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, StackOutputs};
+use miden_prover::ExecutionProof;
+use shared::{
+    input::{keccak256_blocks, Hash, HashInput, Visibility},
+    reference,
+};
+
+use crate::{
+    message::{advice_stack, push_block, MessageWord},
+    program::{self, ProgramStats},
+    trace::TraceShape,
+};
+
+/// Bytes absorbed per permutation, 17 of the state's 25 lanes
+const RATE: usize = 136;
+
+/// The message is private, read from the advice stack and padded by the program.
+pub fn input(n_bytes: usize) -> HashInput {
+    HashInput::new(Hash::Keccak256, n_bytes, Visibility::Private)
+        .with_compressions(keccak256_blocks(n_bytes))
+}
+
+/// The digest at the top of the stack after `keccak(bytes)`, as the high then low
+/// half of each little endian lane
+pub fn expected(bytes: &[u8]) -> Vec<u64> {
+    reference::keccak256(bytes)
+        .chunks(8)
+        .flat_map(|lane| {
+            let lane = u64::from_le_bytes(lane.try_into().unwrap());
+            [lane >> 32, lane & 0xffff_ffff]
+        })
+        .collect()
+}
+
+/// The message as little endian words, with the last one zero padded
+fn words(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks(4)
+        .map(|chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_le_bytes(word)
+        })
+        .collect()
+}
+
+/// An `n_bytes` message padded with Keccak's pad10*1 (0x01, zeros, then 0x80) to
+/// whole blocks, each split into the lane pairs `absorb_lanes` takes, the last of
+/// which only has one lane of message
+fn blocks(n_bytes: usize) -> Vec<[[MessageWord; 4]; 9]> {
+    let block_words = RATE / 4;
+    let n_words = keccak256_blocks(n_bytes) * block_words;
+    // The 0x01 straight after the message and the 0x80 ending the last block, little
+    // endian so the first byte of a word is the low one
+    let pad = |i: usize| {
+        let mut pad = 0;
+        if (i * 4..i * 4 + 4).contains(&n_bytes) {
+            pad |= 0x01 << (8 * (n_bytes - i * 4));
+        }
+        if i == n_words - 1 {
+            pad |= 0x8000_0000;
+        }
+        pad
+    };
+    let words = (0..n_words)
+        .map(|i| match i * 4 {
+            start if start + 4 <= n_bytes => MessageWord::Advice,
+            start if start < n_bytes => MessageWord::Partial {
+                mask: u32::MAX << (8 * (n_bytes - start)),
+                pad: pad(i),
+            },
+            _ => MessageWord::Constant(pad(i)),
+        })
+        .collect::<Vec<_>>();
+
+    words
+        .chunks(block_words)
+        .map(|block| {
+            core::array::from_fn(|pair| {
+                core::array::from_fn(|i| {
+                    block
+                        .get(pair * 4 + i)
+                        .copied()
+                        .unwrap_or(MessageWord::Constant(0))
+                })
+            })
+        })
+        .collect()
+}
+
+/// MASM that absorbs a block into the state at memory address 0, then permutes it
+fn absorb_block(block: &[[MessageWord; 4]; 9]) -> String {
+    let mut code = String::new();
+    for (addr, pair) in block.iter().enumerate() {
+        writeln!(code, "{}push.{addr} exec.absorb_lanes", push_block(pair)).unwrap();
+    }
+    code.push_str("push.0 exec.keccak_p\n");
+    code
+}
+
+/// The program hashing `bytes`, and the advice provider with the message
+fn hash_program(bytes: &[u8]) -> (Program, ProgramStats, MemAdviceProvider) {
+    let blocks = blocks(bytes.len());
+    let full_blocks = bytes.len() / RATE;
+
+    let mut hash_blocks = String::new();
+    if full_blocks > 0 {
+        writeln!(
+            hash_blocks,
+            "repeat.{full_blocks}\n{}end",
+            absorb_block(&blocks[0])
+        )
+        .unwrap();
+    }
+    for block in &blocks[full_blocks..] {
+        hash_blocks.push_str(&absorb_block(block));
+    }
+
+    let code = format!(
+        r#"
+        {procedures}
+
+        begin
+            {hash_blocks}
+
+            padw
+            mem_loadw.1
+            padw
+            mem_loadw.0
+            exec.to_digest
+        end
+    "#,
+        procedures = include_str!("./asm/keccak256.masm"),
+    );
+
+    let (program, stats) = program::compile(&code);
+
+    let pairs = blocks.concat();
+    let advice_provider = MemAdviceProvider::from(
+        AdviceInputs::default()
+            .with_stack_values(advice_stack(&pairs, &words(bytes)))
+            .unwrap(),
+    );
+    (program, stats, advice_provider)
+}
+
+pub fn keccak(
+    bytes: &[u8],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let (program, stats, advice_provider) = hash_program(bytes);
+    let shape = TraceShape::measure(&program, StackInputs::default(), advice_provider.clone());
+
+    (
+        move || {
+            miden::prove(
+                &program,
+                StackInputs::default(),
+                advice_provider.clone(),
+                options.clone(),
+            )
+            .unwrap()
+        },
+        shape,
+        stats,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_match_the_reference() {
+        for n_bytes in [0, 1, 3, 134, 135, 136, 137, 271, 272, 1000] {
+            let bytes = core::iter::repeat_with(|| shared::fastrand::u8(..))
+                .take(n_bytes)
+                .collect::<Vec<_>>();
+            let (program, _, advice_provider) = hash_program(&bytes);
+            let trace = miden_processor::execute(&program, StackInputs::default(), advice_provider)
+                .unwrap();
+
+            let expected = expected(&bytes);
+            assert_eq!(
+                &trace.stack_outputs().stack()[..expected.len()],
+                expected,
+                "digest of {n_bytes} bytes"
+            );
+        }
+    }
+}
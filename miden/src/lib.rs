@@ -1,6 +1,59 @@
+use miden::{ExecutionProof, HashFunction, ProofOptions};
+use miden_crypto::{
+    hash::{
+        blake::{Blake3_192, Blake3_256},
+        rpo::Rpo256,
+    },
+    Felt, StarkField, Word,
+};
+use options::ProofConfig;
+use shared::security::Security;
+
 pub mod assert;
 pub mod blake3;
+pub mod falcon;
 pub mod fib;
+pub mod keccak;
 pub mod merkle;
+mod message;
+pub mod options;
+pub mod program;
+pub mod recursive;
 pub mod rpo;
 pub mod sha;
+pub mod trace;
+
+/// How a word is laid out at the top of the stack outputs, last element on top
+pub(crate) fn word_on_stack(word: Word) -> Vec<u64> {
+    word.iter().rev().map(Felt::as_int).collect()
+}
+
+/// Proof options targeting `bits` of conjectured security, so miden proofs can
+/// be compared with frameworks whose security level isn't configurable.
+pub fn proof_options(bits: u32) -> ProofOptions {
+    match bits {
+        96 => ProofConfig::with_96_bit_security().into(),
+        // 28 queries at blowup 8 plus 17 bits of grinding gives 101 bits, and winterfell
+        // claims one bit less. Blake3_192 is only collision resistant to 96 bits.
+        100 => ProofConfig::with_96_bit_security()
+            .with_num_queries(28)
+            .with_grinding_factor(17)
+            .with_hash_fn(HashFunction::Blake3_256)
+            .into(),
+        128 => ProofConfig::with_128_bit_security().into(),
+        _ => panic!("no proof options for {bits} bit security"),
+    }
+}
+
+/// Conjectured and proven security of `proof`, which depends on its trace length as
+/// well as the proof options.
+pub fn security(proof: &ExecutionProof) -> Security {
+    let stark_proof = proof.stark_proof();
+    let proven = match proof.hash_fn() {
+        HashFunction::Blake3_192 => stark_proof.security_level::<Blake3_192>(false),
+        HashFunction::Blake3_256 => stark_proof.security_level::<Blake3_256>(false),
+        HashFunction::Rpo256 => stark_proof.security_level::<Rpo256>(false),
+    };
+
+    Security::fri(proof.security_level()).with_proven(proven)
+}
@@ -1,69 +1,292 @@
-use miden::Assembler;
-use miden_crypto::merkle::InnerNodeInfo;
-use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, VmStateIterator};
+use miden_crypto::{
+    merkle::{DefaultMerkleStore, InnerNodeInfo, NodeIndex},
+    StarkField,
+};
+use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, StackOutputs};
 use miden_prover::{ExecutionProof, ProofOptions};
 use shared::{
     hash::{rpo::Rpo, HashFn},
-    Tree,
+    reference, Tree,
+};
+
+use crate::{
+    program::{self, ProgramStats},
+    trace::TraceShape,
+    word_on_stack,
 };
 
+/// The root of the tree `digest` is in at position `index`, given its authentication
+/// `path`. `membership` leaves it at the top of the stack.
+pub fn membership_expected(
+    path: &[<Rpo as HashFn>::Digest],
+    digest: <Rpo as HashFn>::Digest,
+    index: u64,
+) -> Vec<u64> {
+    word_on_stack(*reference::merkle_path::<Rpo>(digest, index, path))
+}
+
+/// Verifies that `digest` is the leaf at position `index` of the tree with the public
+/// `root`. The path is read from the advice stack and the bits of `index` choose
+/// which side of each merge the running digest goes on.
 pub fn membership(
     path: Vec<<Rpo as HashFn>::Digest>,
     digest: <Rpo as HashFn>::Digest,
-) -> (impl Fn() -> ExecutionProof, VmStateIterator) {
-    assert_eq!(path.len(), 10);
+    index: u64,
+    root: <Rpo as HashFn>::Digest,
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let depth = path.len();
+    assert!(depth <= 64, "index is a u64");
     let mut advice = AdviceInputs::default();
-    advice.extend_stack(path.iter().flat_map(|digest| digest.as_elements()).copied());
     advice.extend_stack(digest.as_elements().iter().copied());
+    advice.extend_stack(path.iter().flat_map(|digest| digest.as_elements()).copied());
     let advice = MemAdviceProvider::from(advice);
 
-    let program = Assembler::default()
-        .compile(include_str!("./asm/membership.masm"))
-        .unwrap();
+    // The index doesn't fit in a field element, so it's split into u32 limbs that
+    // are shifted out a bit per level
+    let verify_levels = |levels: usize| {
+        if levels == 0 {
+            return String::new();
+        }
+
+        format!(
+            r#"
+            repeat.{levels}
+                adv_push.4
+                # => [SIBLING, DIGEST, index, ...]
+                dup.8 push.1 u32checked_and
+                if.true
+                    swapw
+                end
+                hmerge
+                movup.4 u32checked_shr.1 movdn.4
+            end
+        "#
+        )
+    };
+    let code = format!(
+        r#"
+        begin
+            adv_push.4
+            # => [DIGEST, index_lo, index_hi, ROOT, ...]
+            {verify_lo}
+            movup.4 assertz
+            {verify_hi}
+            movup.4 assertz
+            # => [DIGEST, ROOT, ...]
+            dupw.1 assert_eqw
+        end
+    "#,
+        verify_lo = verify_levels(depth.min(32)),
+        verify_hi = verify_levels(depth.saturating_sub(32)),
+    );
+    let (program, stats) = program::compile(&code);
 
-    let stack = StackInputs::default();
-    let opts = ProofOptions::default();
+    let mut stack = vec![index as u32 as u64, index >> 32];
+    stack.extend(word_on_stack(*root));
+    let stack = StackInputs::try_from_values(stack.into_iter().rev()).unwrap();
 
-    let vm_iter = miden_processor::execute_iter(&program, stack.clone(), advice.clone());
+    let shape = TraceShape::measure(&program, stack.clone(), advice.clone());
 
     let prove = move || {
-        miden_prover::prove(&program, stack.clone(), advice.clone(), opts.clone())
-            .unwrap()
-            .1
+        miden_prover::prove(&program, stack.clone(), advice.clone(), options.clone()).unwrap()
     };
 
-    (prove, vm_iter)
+    (prove, shape, stats)
+}
+
+/// The root at the top of the stack after `merge_trees(tree1, tree2)`
+pub fn merge_trees_expected(tree1: &Tree<Rpo>, tree2: &Tree<Rpo>) -> Vec<u64> {
+    word_on_stack(*Rpo::merge(tree1.digest(), tree2.digest()))
 }
 
 pub fn merge_trees(
     tree1: &Tree<Rpo>,
     tree2: &Tree<Rpo>,
-) -> (impl Fn() -> ExecutionProof, VmStateIterator) {
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
     let mut advice = AdviceInputs::default();
     advice.extend_merkle_store(tree1.iter().map(inner_node_info));
     advice.extend_merkle_store(tree2.iter().map(inner_node_info));
 
     let advice = MemAdviceProvider::from(advice);
-    let program = Assembler::default()
-        .compile("begin mtree_merge end")
-        .unwrap();
+    let (program, stats) = program::compile("begin mtree_merge end");
     let stack = [tree1, tree2]
         .into_iter()
         .flat_map(|tree| tree.digest().as_elements().to_owned())
         .collect();
 
     let stack = StackInputs::new(stack);
-    let opts = ProofOptions::default();
 
-    let vm_iter = miden_processor::execute_iter(&program, stack.clone(), advice.clone());
+    let shape = TraceShape::measure(&program, stack.clone(), advice.clone());
 
     let prove = move || {
-        let (_stack, proof) =
-            miden_prover::prove(&program, stack.clone(), advice.clone(), opts.clone()).unwrap();
-        proof
+        miden_prover::prove(&program, stack.clone(), advice.clone(), options.clone()).unwrap()
     };
 
-    (prove, vm_iter)
+    (prove, shape, stats)
+}
+
+/// The root at the top of the stack after `mtree_get` or `mtree_verify`, which leave
+/// the tree unchanged
+pub fn root_expected(tree: &Tree<Rpo>) -> Vec<u64> {
+    word_on_stack(*tree.digest())
+}
+
+/// The root at the top of the stack after `mtree_set(tree, updates)`
+pub fn mtree_set_expected(
+    tree: &Tree<Rpo>,
+    updates: &[(u64, <Rpo as HashFn>::Digest)],
+) -> Vec<u64> {
+    let depth = depth(tree);
+    let mut store = DefaultMerkleStore::new();
+    store.extend(tree.iter().map(inner_node_info));
+    let root = updates.iter().fold(tree.digest(), |root, (index, value)| {
+        let index = NodeIndex::new(depth, *index).unwrap();
+        store.set_node(root, index, *value).unwrap().root
+    });
+
+    word_on_stack(*root)
+}
+
+/// Reads the leaves at `indices` with `mtree_get`, asserting each one matches the tree
+pub fn mtree_get(
+    tree: &Tree<Rpo>,
+    indices: &[u64],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let depth = depth(tree);
+    let code = indices
+        .iter()
+        .map(|&index| {
+            format!(
+                "push.{index}.{depth} mtree_get push.{leaf} assert_eqw\n",
+                leaf = push_word(leaf(tree, depth, index)),
+            )
+        })
+        .collect();
+
+    store_program(tree, code, options)
+}
+
+/// Replaces leaves with `mtree_set`, one `(index, value)` update at a time
+pub fn mtree_set(
+    tree: &Tree<Rpo>,
+    updates: &[(u64, <Rpo as HashFn>::Digest)],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let depth = depth(tree);
+    let code = updates
+        .iter()
+        .map(|&(index, value)| {
+            format!(
+                "push.{value} swapw push.{index}.{depth} mtree_set dropw\n",
+                value = push_word(value),
+            )
+        })
+        .collect();
+
+    store_program(tree, code, options)
+}
+
+/// Checks that the leaves at `indices` open to the root with `mtree_verify`
+pub fn mtree_verify(
+    tree: &Tree<Rpo>,
+    indices: &[u64],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let depth = depth(tree);
+    let code = indices
+        .iter()
+        .map(|&index| {
+            format!(
+                "push.{index}.{depth} push.{leaf} mtree_verify dropw drop drop\n",
+                leaf = push_word(leaf(tree, depth, index)),
+            )
+        })
+        .collect();
+
+    store_program(tree, code, options)
+}
+
+/// Runs `code` on a stack holding the root of `tree`, with all of its nodes in the
+/// advice provider's Merkle store
+fn store_program(
+    tree: &Tree<Rpo>,
+    code: String,
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let mut advice = AdviceInputs::default();
+    advice.extend_merkle_store(tree.iter().map(inner_node_info));
+
+    let advice = MemAdviceProvider::from(advice);
+    let (program, stats) = program::compile(&format!("begin {code} end"));
+    let stack = StackInputs::new(tree.digest().as_elements().to_owned());
+
+    let shape = TraceShape::measure(&program, stack.clone(), advice.clone());
+
+    let prove = move || {
+        miden_prover::prove(&program, stack.clone(), advice.clone(), options.clone()).unwrap()
+    };
+
+    (prove, shape, stats)
+}
+
+/// The immediate value that `push` needs to put `word` on the stack
+fn push_word(word: <Rpo as HashFn>::Digest) -> String {
+    word.as_elements()
+        .iter()
+        .map(|element| element.as_int().to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Depth of the leaves of `tree`, which is perfectly balanced
+fn depth(tree: &Tree<Rpo>) -> u8 {
+    match tree {
+        Tree::Leaf(_) => 0,
+        Tree::Node { left, .. } => depth(left) + 1,
+    }
+}
+
+/// The leaf at `index`, where the most significant of `depth` bits picks the
+/// root's child
+fn leaf(tree: &Tree<Rpo>, depth: u8, index: u64) -> <Rpo as HashFn>::Digest {
+    match tree {
+        Tree::Leaf(digest) => *digest,
+        Tree::Node { left, right, .. } => {
+            let child = if index >> (depth - 1) & 1 == 0 {
+                left
+            } else {
+                right
+            };
+            leaf(child, depth - 1, index)
+        }
+    }
 }
 
 fn inner_node_info(tree: &Tree<Rpo>) -> InnerNodeInfo {
@@ -0,0 +1,69 @@
+//! Loading a (padded) message from the advice stack, one block of u32 words at a time.
+//! The message length is fixed when the program is generated, so which words come
+//! from the prover and which are padding is known up front.
+
+use std::fmt::Write;
+
+/// A 32-bit word of a padded message block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MessageWord {
+    /// Four message bytes, read from the advice stack
+    Advice,
+    /// The last, partial, word of the message, read from the advice stack. The bits
+    /// in `mask` are past the end of the message and must be zero, then `pad` is or-ed in.
+    Partial {
+        mask: u32,
+        pad: u32,
+    },
+    Constant(u32),
+}
+
+/// MASM that pushes a block of message words (a multiple of 4) onto the stack, with
+/// the first word on top, asserting that the words read from the advice stack are
+/// valid u32s.
+pub(crate) fn push_block(block: &[MessageWord]) -> String {
+    assert_eq!(block.len() % 4, 0);
+
+    if block.iter().all(|word| *word == MessageWord::Advice) {
+        return match block.len() / 4 {
+            1 => "adv_push.4 u32assertw\n".to_string(),
+            n => format!("repeat.{n} adv_push.4 u32assertw end\n"),
+        };
+    }
+
+    // The deepest word is pushed first
+    let mut code = String::new();
+    for word in block.iter().rev() {
+        match word {
+            MessageWord::Advice => code.push_str("adv_push.1 u32assert\n"),
+            MessageWord::Partial { mask, pad } => {
+                writeln!(code, "adv_push.1 dup push.{mask} u32checked_and assertz").unwrap();
+                if *pad != 0 {
+                    writeln!(code, "push.{pad} u32checked_or").unwrap();
+                }
+            }
+            MessageWord::Constant(value) => writeln!(code, "push.{value}").unwrap(),
+        }
+    }
+
+    code
+}
+
+/// The advice stack that `push_block` reads `words` (the message split into u32s,
+/// with the last one zero padded) from, given the padded blocks.
+pub(crate) fn advice_stack<const N: usize>(blocks: &[[MessageWord; N]], words: &[u32]) -> Vec<u64> {
+    let mut words = words.iter();
+    blocks
+        .iter()
+        .flat_map(|block| {
+            // `push_block` pushes the last word of each block first
+            let mut block_words = block
+                .iter()
+                .filter(|word| !matches!(word, MessageWord::Constant(_)))
+                .map(|_| u64::from(*words.next().unwrap()))
+                .collect::<Vec<_>>();
+            block_words.reverse();
+            block_words
+        })
+        .collect()
+}
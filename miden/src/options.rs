@@ -0,0 +1,101 @@
+use miden::{FieldExtension, HashFunction, ProofOptions};
+
+/// The STARK parameters miden proves with. Start from one of miden's security
+/// presets and override individual parameters, e.g.
+/// `ProofConfig::with_96_bit_security().with_blowup_factor(16).into()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofConfig {
+    pub num_queries: usize,
+    pub blowup_factor: usize,
+    pub grinding_factor: u32,
+    pub field_extension: FieldExtension,
+    pub fri_folding_factor: usize,
+    pub fri_max_remainder_size: usize,
+    /// Hash used for the trace commitments and FRI. RPO makes the proof cheaper to
+    /// verify inside the VM, Blake3 makes it cheaper to generate.
+    pub hash_fn: HashFunction,
+}
+
+impl ProofConfig {
+    /// Same as [ProofOptions::with_96_bit_security], which is what miden uses by default
+    pub fn with_96_bit_security() -> Self {
+        Self {
+            num_queries: 27,
+            blowup_factor: 8,
+            grinding_factor: 16,
+            field_extension: FieldExtension::Quadratic,
+            fri_folding_factor: 8,
+            fri_max_remainder_size: 255,
+            hash_fn: HashFunction::Blake3_192,
+        }
+    }
+
+    /// Same as [ProofOptions::with_128_bit_security]
+    pub fn with_128_bit_security() -> Self {
+        Self {
+            num_queries: 27,
+            blowup_factor: 16,
+            grinding_factor: 21,
+            field_extension: FieldExtension::Cubic,
+            fri_folding_factor: 8,
+            fri_max_remainder_size: 255,
+            hash_fn: HashFunction::Blake3_256,
+        }
+    }
+
+    pub fn with_num_queries(mut self, num_queries: usize) -> Self {
+        self.num_queries = num_queries;
+        self
+    }
+
+    pub fn with_blowup_factor(mut self, blowup_factor: usize) -> Self {
+        self.blowup_factor = blowup_factor;
+        self
+    }
+
+    pub fn with_grinding_factor(mut self, grinding_factor: u32) -> Self {
+        self.grinding_factor = grinding_factor;
+        self
+    }
+
+    pub fn with_fri_folding_factor(mut self, fri_folding_factor: usize) -> Self {
+        self.fri_folding_factor = fri_folding_factor;
+        self
+    }
+
+    pub fn with_field_extension(mut self, field_extension: FieldExtension) -> Self {
+        self.field_extension = field_extension;
+        self
+    }
+
+    /// Has to be one less than a power of two, and at most 255
+    pub fn with_fri_max_remainder_size(mut self, fri_max_remainder_size: usize) -> Self {
+        self.fri_max_remainder_size = fri_max_remainder_size;
+        self
+    }
+
+    pub fn with_hash_fn(mut self, hash_fn: HashFunction) -> Self {
+        self.hash_fn = hash_fn;
+        self
+    }
+}
+
+impl Default for ProofConfig {
+    fn default() -> Self {
+        Self::with_96_bit_security()
+    }
+}
+
+impl From<ProofConfig> for ProofOptions {
+    fn from(config: ProofConfig) -> Self {
+        ProofOptions::new(
+            config.num_queries,
+            config.blowup_factor,
+            config.grinding_factor,
+            config.field_extension,
+            config.fri_folding_factor,
+            config.fri_max_remainder_size,
+            config.hash_fn,
+        )
+    }
+}
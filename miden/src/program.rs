@@ -0,0 +1,507 @@
+//! Compiling MASM with an on-disk cache of the compiled programs, keyed by the source
+//! and the miden version. Unrolled `repeat` loops make for huge programs, so repeated
+//! runs of the suite would otherwise spend a lot of time in the assembler.
+//!
+//! miden 0.6 can't serialize programs itself, so the MAST is written out here. A cached
+//! program is only used if it hashes to the same value as when it was compiled.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use miden::{Assembler, Digest, Program};
+use miden_core::{
+    code_blocks::CodeBlock, AdviceInjector, CodeBlockTable, Decorator, DecoratorList, Kernel,
+    Operation,
+};
+use miden_crypto::{
+    hash::{blake::Blake3_256, rpo::RpoDigest},
+    utils::{
+        ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
+    },
+    Felt, FieldElement,
+};
+
+/// The locked miden-vm version, from the build script. Programs compiled by other
+/// versions aren't reused.
+const MIDEN_VERSION: &str = env!("MIDEN_VM_VERSION");
+
+/// How a program was compiled, and how big it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramStats {
+    /// Time the assembler took, which is recorded in the cache along with the program
+    pub compile_time: Duration,
+    /// Whether the program was loaded from the cache instead
+    pub cached: bool,
+    /// Code blocks of every kind in the program's MAST. `repeat` bodies are copied
+    /// once per iteration, unless the body is a single span.
+    pub mast_nodes: usize,
+    /// Span blocks, which hold all of the program's straight line code
+    pub span_blocks: usize,
+    /// Operations in span blocks, including the noops that batching adds
+    pub operations: usize,
+    /// Size of the program in the cache, if it could be serialized
+    pub serialized_size: Option<usize>,
+    pub hash: Digest,
+}
+
+impl ProgramStats {
+    fn new(
+        program: &Program,
+        compile_time: Duration,
+        cached: bool,
+        serialized_size: Option<usize>,
+    ) -> Self {
+        let mut stats = Self {
+            compile_time,
+            cached,
+            mast_nodes: 0,
+            span_blocks: 0,
+            operations: 0,
+            serialized_size,
+            hash: program.hash(),
+        };
+        stats.count(program.root());
+        stats
+    }
+
+    fn count(&mut self, block: &CodeBlock) {
+        self.mast_nodes += 1;
+        match block {
+            CodeBlock::Span(span) => {
+                self.span_blocks += 1;
+                self.operations += span
+                    .op_batches()
+                    .iter()
+                    .map(|batch| batch.ops().len())
+                    .sum::<usize>();
+            }
+            CodeBlock::Join(join) => {
+                self.count(join.first());
+                self.count(join.second());
+            }
+            CodeBlock::Split(split) => {
+                self.count(split.on_true());
+                self.count(split.on_false());
+            }
+            CodeBlock::Loop(body) => self.count(body.body()),
+            CodeBlock::Call(_) | CodeBlock::Proxy(_) => {}
+        }
+    }
+
+    /// The program hash as hex
+    pub fn hash_hex(&self) -> String {
+        let bytes: [u8; 32] = self.hash.into();
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+/// Compiles `source`, with the standard library available, unless it's in the cache.
+/// Set `MIDEN_PROGRAM_CACHE` to change where the cache is kept.
+pub fn compile(source: &str) -> (Program, ProgramStats) {
+    compile_in(&cache_dir(), source)
+}
+
+fn compile_in(cache_dir: &Path, source: &str) -> (Program, ProgramStats) {
+    let path = cache_dir.join(format!("{}.mast", cache_key(source)));
+    if let Ok(bytes) = fs::read(&path) {
+        if let Some((program, compile_time)) = load(&bytes) {
+            let stats = ProgramStats::new(&program, compile_time, true, Some(bytes.len()));
+            return (program, stats);
+        }
+    }
+
+    let start = Instant::now();
+    let program = Assembler::default()
+        .with_library(&miden_stdlib::StdLibrary::default())
+        .unwrap()
+        .compile(source)
+        .unwrap();
+    let compile_time = start.elapsed();
+
+    // Programs with debug info can't be cached, and the cache is only an optimisation
+    let bytes = save(&program, compile_time);
+    if let Some(bytes) = &bytes {
+        if fs::create_dir_all(cache_dir).is_ok() {
+            let _ = fs::write(&path, bytes);
+        }
+    }
+
+    let stats = ProgramStats::new(
+        &program,
+        compile_time,
+        false,
+        bytes.map(|bytes| bytes.len()),
+    );
+    (program, stats)
+}
+
+fn cache_dir() -> PathBuf {
+    env::var_os("MIDEN_PROGRAM_CACHE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/program-cache"))
+}
+
+fn cache_key(source: &str) -> String {
+    let hash = Blake3_256::hash(format!("{MIDEN_VERSION}\n{source}").as_bytes());
+    hash.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn save(program: &Program, compile_time: Duration) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    bytes.write_u64(compile_time.as_nanos() as u64);
+    program.hash().write_into(&mut bytes);
+    program.kernel().write_into(&mut bytes);
+    write_block(&mut bytes, program.root())?;
+
+    // Procedures that are `call`ed aren't part of the root block
+    let mut called = Vec::new();
+    collect_calls(program.root(), program.cb_table(), &mut called);
+    bytes.write_u64(called.len() as u64);
+    for block in called {
+        write_block(&mut bytes, block)?;
+    }
+
+    Some(bytes)
+}
+
+fn load(bytes: &[u8]) -> Option<(Program, Duration)> {
+    let mut source = SliceReader::new(bytes);
+    let compile_time = Duration::from_nanos(source.read_u64().ok()?);
+    let hash = RpoDigest::read_from(&mut source).ok()?;
+    let kernel = Kernel::read_from(&mut source).ok()?;
+    let root = read_block(&mut source).ok()?;
+
+    let mut cb_table = CodeBlockTable::default();
+    for _ in 0..source.read_u64().ok()? {
+        cb_table.insert(read_block(&mut source).ok()?);
+    }
+
+    let program = Program::with_kernel(root, kernel, cb_table);
+    (program.hash() == hash).then_some((program, compile_time))
+}
+
+fn collect_calls<'a>(
+    block: &'a CodeBlock,
+    cb_table: &'a CodeBlockTable,
+    out: &mut Vec<&'a CodeBlock>,
+) {
+    match block {
+        CodeBlock::Span(_) | CodeBlock::Proxy(_) => {}
+        CodeBlock::Join(join) => {
+            collect_calls(join.first(), cb_table, out);
+            collect_calls(join.second(), cb_table, out);
+        }
+        CodeBlock::Split(split) => {
+            collect_calls(split.on_true(), cb_table, out);
+            collect_calls(split.on_false(), cb_table, out);
+        }
+        CodeBlock::Loop(body) => collect_calls(body.body(), cb_table, out),
+        CodeBlock::Call(call) => {
+            // Syscalls are to kernel procedures, which aren't in the table
+            if let Some(callee) = cb_table.get(call.fn_hash()) {
+                if !out.iter().any(|block| block.hash() == callee.hash()) {
+                    out.push(callee);
+                    collect_calls(callee, cb_table, out);
+                }
+            }
+        }
+    }
+}
+
+// MAST ENCODING
+// ================================================================================================
+
+const SPAN: u8 = 0;
+const JOIN: u8 = 1;
+const SPLIT: u8 = 2;
+const LOOP: u8 = 3;
+const CALL: u8 = 4;
+const SYSCALL: u8 = 5;
+const PROXY: u8 = 6;
+
+fn write_block(target: &mut Vec<u8>, block: &CodeBlock) -> Option<()> {
+    match block {
+        CodeBlock::Span(span) => {
+            target.write_u8(SPAN);
+            // Re-batching the ops, including the noops that batching added, gives the
+            // same batches, which is also how `Span::replicate` works
+            let ops = span
+                .op_batches()
+                .iter()
+                .flat_map(|batch| batch.ops())
+                .collect::<Vec<_>>();
+            target.write_u64(ops.len() as u64);
+            for op in ops {
+                target.write_u8(op.op_code());
+                if let Operation::Push(value) = op {
+                    value.write_into(target);
+                }
+            }
+
+            target.write_u64(span.decorators().len() as u64);
+            for (index, decorator) in span.decorators() {
+                target.write_u64(*index as u64);
+                write_decorator(target, decorator)?;
+            }
+        }
+        CodeBlock::Join(join) => {
+            target.write_u8(JOIN);
+            write_block(target, join.first())?;
+            write_block(target, join.second())?;
+        }
+        CodeBlock::Split(split) => {
+            target.write_u8(SPLIT);
+            write_block(target, split.on_true())?;
+            write_block(target, split.on_false())?;
+        }
+        CodeBlock::Loop(body) => {
+            target.write_u8(LOOP);
+            write_block(target, body.body())?;
+        }
+        CodeBlock::Call(call) => {
+            target.write_u8(if call.is_syscall() { SYSCALL } else { CALL });
+            call.fn_hash().write_into(target);
+        }
+        CodeBlock::Proxy(proxy) => {
+            target.write_u8(PROXY);
+            proxy.hash().write_into(target);
+        }
+    }
+
+    Some(())
+}
+
+fn read_block<R: ByteReader>(source: &mut R) -> Result<CodeBlock, DeserializationError> {
+    let block = match source.read_u8()? {
+        SPAN => {
+            let mut ops = Vec::new();
+            for _ in 0..source.read_u64()? {
+                ops.push(read_operation(source)?);
+            }
+            let mut decorators = DecoratorList::new();
+            for _ in 0..source.read_u64()? {
+                let index = source.read_u64()? as usize;
+                decorators.push((index, read_decorator(source)?));
+            }
+            CodeBlock::new_span_with_decorators(ops, decorators)
+        }
+        JOIN => CodeBlock::new_join([read_block(source)?, read_block(source)?]),
+        SPLIT => CodeBlock::new_split(read_block(source)?, read_block(source)?),
+        LOOP => CodeBlock::new_loop(read_block(source)?),
+        CALL => CodeBlock::new_call(RpoDigest::read_from(source)?),
+        SYSCALL => CodeBlock::new_syscall(RpoDigest::read_from(source)?),
+        PROXY => CodeBlock::new_proxy(RpoDigest::read_from(source)?),
+        tag => {
+            return Err(DeserializationError::InvalidValue(format!(
+                "unknown code block {tag}"
+            )))
+        }
+    };
+
+    Ok(block)
+}
+
+/// Every operation that can appear in a span block, to look up op codes in
+const SPAN_OPERATIONS: [Operation; 77] = {
+    use Operation::*;
+    [
+        Noop,
+        Assert,
+        FmpAdd,
+        FmpUpdate,
+        SDepth,
+        Caller,
+        Clk,
+        Add,
+        Neg,
+        Mul,
+        Inv,
+        Incr,
+        And,
+        Or,
+        Not,
+        Eq,
+        Eqz,
+        Expacc,
+        Ext2Mul,
+        U32split,
+        U32add,
+        U32assert2,
+        U32add3,
+        U32sub,
+        U32mul,
+        U32madd,
+        U32div,
+        U32and,
+        U32xor,
+        Pad,
+        Drop,
+        Dup0,
+        Dup1,
+        Dup2,
+        Dup3,
+        Dup4,
+        Dup5,
+        Dup6,
+        Dup7,
+        Dup9,
+        Dup11,
+        Dup13,
+        Dup15,
+        Swap,
+        SwapW,
+        SwapW2,
+        SwapW3,
+        SwapDW,
+        MovUp2,
+        MovUp3,
+        MovUp4,
+        MovUp5,
+        MovUp6,
+        MovUp7,
+        MovUp8,
+        MovDn2,
+        MovDn3,
+        MovDn4,
+        MovDn5,
+        MovDn6,
+        MovDn7,
+        MovDn8,
+        CSwap,
+        CSwapW,
+        Push(Felt::ZERO),
+        AdvPop,
+        AdvPopW,
+        MLoadW,
+        MStoreW,
+        MLoad,
+        MStore,
+        MStream,
+        Pipe,
+        HPerm,
+        MpVerify,
+        MrUpdate,
+        FriE2F4,
+    ]
+};
+
+fn read_operation<R: ByteReader>(source: &mut R) -> Result<Operation, DeserializationError> {
+    let op_code = source.read_u8()?;
+    match SPAN_OPERATIONS.iter().find(|op| op.op_code() == op_code) {
+        Some(Operation::Push(_)) => Ok(Operation::Push(Felt::read_from(source)?)),
+        Some(op) => Ok(*op),
+        None => Err(DeserializationError::InvalidValue(format!(
+            "unknown op code {op_code}"
+        ))),
+    }
+}
+
+fn write_decorator(target: &mut Vec<u8>, decorator: &Decorator) -> Option<()> {
+    let Decorator::Advice(injector) = decorator else {
+        return None;
+    };
+
+    match injector {
+        AdviceInjector::MerkleNodeMerge => target.write_u8(0),
+        AdviceInjector::MerkleNodeToStack => target.write_u8(1),
+        AdviceInjector::MapValueToStack {
+            include_len,
+            key_offset,
+        } => {
+            target.write_u8(2);
+            target.write_bool(*include_len);
+            target.write_u64(*key_offset as u64);
+        }
+        AdviceInjector::DivU64 => target.write_u8(3),
+        AdviceInjector::Ext2Inv => target.write_u8(4),
+        AdviceInjector::Ext2Intt => target.write_u8(5),
+        AdviceInjector::SmtGet => target.write_u8(6),
+        AdviceInjector::MemToMap => target.write_u8(7),
+        AdviceInjector::HdwordToMap { domain } => {
+            target.write_u8(8);
+            domain.write_into(target);
+        }
+    }
+
+    Some(())
+}
+
+fn read_decorator<R: ByteReader>(source: &mut R) -> Result<Decorator, DeserializationError> {
+    let injector = match source.read_u8()? {
+        0 => AdviceInjector::MerkleNodeMerge,
+        1 => AdviceInjector::MerkleNodeToStack,
+        2 => AdviceInjector::MapValueToStack {
+            include_len: source.read_bool()?,
+            key_offset: source.read_u64()? as usize,
+        },
+        3 => AdviceInjector::DivU64,
+        4 => AdviceInjector::Ext2Inv,
+        5 => AdviceInjector::Ext2Intt,
+        6 => AdviceInjector::SmtGet,
+        7 => AdviceInjector::MemToMap,
+        8 => AdviceInjector::HdwordToMap {
+            domain: Felt::read_from(source)?,
+        },
+        tag => {
+            return Err(DeserializationError::InvalidValue(format!(
+                "unknown advice injector {tag}"
+            )))
+        }
+    };
+
+    Ok(Decorator::Advice(injector))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "
+        use.std::math::u64
+
+        proc.double
+            dup add
+        end
+
+        begin
+            push.10.0.3.0
+            exec.u64::unchecked_div
+            drop
+            while.true
+                call.double
+                dup push.1000 lt
+            end
+            if.true push.1 else push.2 end
+        end
+    ";
+
+    #[test]
+    fn cached_programs_round_trip() {
+        let cache = env::temp_dir().join(format!("miden-program-cache-{}", std::process::id()));
+
+        let (program, stats) = compile_in(&cache, SOURCE);
+        let (cached_program, cached_stats) = compile_in(&cache, SOURCE);
+        let _ = fs::remove_dir_all(&cache);
+
+        assert!(!stats.cached);
+        assert!(cached_stats.cached);
+        assert_eq!(cached_program.hash(), program.hash());
+        assert_eq!(cached_stats.mast_nodes, stats.mast_nodes);
+        assert_eq!(cached_stats.operations, stats.operations);
+    }
+
+    #[test]
+    fn programs_with_debug_info_are_not_cached() {
+        let program = Assembler::default()
+            .with_debug_mode(true)
+            .with_library(&miden_stdlib::StdLibrary::default())
+            .unwrap()
+            .compile(SOURCE)
+            .unwrap();
+
+        assert!(save(&program, Duration::ZERO).is_none());
+    }
+}
@@ -0,0 +1,345 @@
+//! Verifying a miden proof inside miden with `std::crypto::stark::verifier`.
+//!
+//! The verifier reads the proof from the advice provider: the commitments, OOD frame
+//! and FRI remainder from the advice stack, the queried rows from the advice map and
+//! their authentication paths from the Merkle store. Building that advice follows the
+//! recursive verifier test in miden-stdlib 0.5.
+//!
+//! The stdlib verifier doesn't yet evaluate the constraints at the OOD point, so its
+//! cost is a lower bound on what a complete recursive verifier would take. It only
+//! accepts proofs made with [inner_options], of programs with exactly 16 stack inputs
+//! and outputs.
+
+use std::time::{Duration, Instant};
+
+use miden::{FieldExtension, HashFunction, ProgramInfo};
+use miden_air::{ProcessorAir, PublicInputs};
+use miden_core::ToElements;
+use miden_crypto::{
+    hash::rpo::{Rpo256, RpoDigest},
+    merkle::{MerklePath, MerklePathSet, MerkleStore},
+    Felt, FieldElement, RandomCoin, StarkField,
+};
+use miden_processor::{
+    crypto::RpoRandomCoin, math::fft, AdviceInputs, MemAdviceProvider, QuadExtension, StackInputs,
+    StackOutputs,
+};
+use miden_prover::{ExecutionProof, ProofOptions};
+use winter_air::{
+    proof::{StarkProof, Table},
+    Air,
+};
+use winter_crypto::BatchMerkleProof;
+use winter_fri::folding::fold_positions;
+
+use crate::{
+    program::{self, ProgramStats},
+    trace::TraceShape,
+};
+
+type QuadExt = QuadExtension<Felt>;
+
+/// The FRI folding factor the verifier is written for
+const FOLDING_FACTOR: usize = 4;
+
+/// The only proof options the stdlib verifier accepts: blowup 8, FRI folding by 4 down
+/// to a remainder of degree 7, the quadratic extension and RPO.
+pub fn inner_options() -> ProofOptions {
+    ProofOptions::new(
+        43,
+        8,
+        12,
+        FieldExtension::Quadratic,
+        FOLDING_FACTOR,
+        7,
+        HashFunction::Rpo256,
+    )
+}
+
+/// A proof to be verified recursively, along with its public inputs
+pub struct InnerProof {
+    pub proof: ExecutionProof,
+    pub program_info: ProgramInfo,
+    pub stack_inputs: StackInputs,
+    pub stack_outputs: StackOutputs,
+    pub shape: TraceShape,
+    pub proving_time: Duration,
+}
+
+impl InnerProof {
+    /// Proves the `assert` program, with 16 zeros as the stack inputs
+    pub fn assert(a: u32, b: u32) -> Self {
+        let (program, _) = program::compile(crate::assert::CODE);
+        let stack_inputs = StackInputs::try_from_values([0; 16]).unwrap();
+        let advice_provider = MemAdviceProvider::from(
+            AdviceInputs::default()
+                .with_stack_values(vec![a as u64, b as u64])
+                .unwrap(),
+        );
+        let shape = TraceShape::measure(&program, stack_inputs.clone(), advice_provider.clone());
+
+        let start = Instant::now();
+        let (stack_outputs, proof) = miden_prover::prove(
+            &program,
+            stack_inputs.clone(),
+            advice_provider,
+            inner_options(),
+        )
+        .unwrap();
+        let proving_time = start.elapsed();
+
+        Self {
+            proof,
+            program_info: ProgramInfo::from(program),
+            stack_inputs,
+            stack_outputs,
+            shape,
+            proving_time,
+        }
+    }
+}
+
+/// Verifies `inner` with the stdlib verifier, which leaves an empty stack.
+pub fn verify(
+    inner: &InnerProof,
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let code = r#"
+        use.std::crypto::stark::verifier
+
+        begin
+            exec.verifier::verify
+        end
+    "#;
+
+    let (program, stats) = program::compile(code);
+
+    let pub_inputs = PublicInputs::new(
+        inner.program_info.clone(),
+        inner.stack_inputs.clone(),
+        inner.stack_outputs.clone(),
+    );
+    let (stack_inputs, advice) = verifier_inputs(inner.proof.stark_proof().clone(), pub_inputs);
+    let advice = MemAdviceProvider::from(advice);
+
+    let shape = TraceShape::measure(&program, stack_inputs.clone(), advice.clone());
+
+    let prove = move || {
+        miden_prover::prove(
+            &program,
+            stack_inputs.clone(),
+            advice.clone(),
+            options.clone(),
+        )
+        .unwrap()
+    };
+
+    (prove, shape, stats)
+}
+
+/// Replays the verifier's side of the protocol on the host to find the query
+/// positions, and lays out the proof the way `verifier::verify` reads it.
+fn verifier_inputs(proof: StarkProof, pub_inputs: PublicInputs) -> (StackInputs, AdviceInputs) {
+    let pub_inputs_elements = pub_inputs.to_elements();
+    let air = ProcessorAir::new(proof.get_trace_info(), pub_inputs, proof.options().clone());
+    let StarkProof {
+        context,
+        commitments,
+        mut trace_queries,
+        constraint_queries,
+        ood_frame,
+        fri_proof,
+        pow_nonce,
+    } = proof;
+
+    let lde_domain_size = air.lde_domain_size();
+    let num_queries = air.options().num_queries();
+    let fri_options = air.options().to_fri_options();
+
+    // The public coin is seeded with the proof context and public inputs, the first
+    // of which are [grinding_factor, blowup_factor, num_queries, trace_length]
+    let mut seed: Vec<Felt> = context.to_elements();
+    let stack = vec![
+        seed[4].as_int(),
+        seed[5].as_int().ilog2() as u64,
+        seed[6].as_int(),
+        seed[7].as_int().ilog2() as u64,
+    ];
+    seed.extend(&pub_inputs_elements);
+    let mut coin = RpoRandomCoin::new(&seed);
+    let mut tape = to_ints(&pub_inputs_elements);
+
+    // Trace and constraint commitments
+    let (trace_roots, constraint_root, fri_roots) = commitments
+        .parse::<Rpo256>(
+            air.trace_layout().num_segments(),
+            fri_options.num_fri_layers(lde_domain_size),
+        )
+        .unwrap();
+    tape.extend(
+        trace_roots
+            .iter()
+            .flat_map(|root| to_ints(root.as_elements())),
+    );
+    coin.reseed(trace_roots[0]);
+    for (i, root) in trace_roots.iter().skip(1).enumerate() {
+        air.get_aux_trace_segment_random_elements::<QuadExt, _>(i, &mut coin)
+            .unwrap();
+        coin.reseed(*root);
+    }
+    air.get_constraint_composition_coefficients::<QuadExt, _>(&mut coin)
+        .unwrap();
+    tape.extend(to_ints(constraint_root.as_elements()));
+    coin.reseed(constraint_root);
+
+    // Out of domain evaluations
+    let (ood_trace, ood_constraints) = ood_frame
+        .parse::<QuadExt>(
+            air.trace_layout().main_trace_width(),
+            air.trace_layout().aux_trace_width(),
+            air.ce_blowup_factor(),
+        )
+        .unwrap();
+    tape.extend(to_ints(QuadExt::slice_as_base_elements(&ood_trace)));
+    coin.reseed(Rpo256::hash_elements(&ood_trace));
+    tape.extend(to_ints(QuadExt::slice_as_base_elements(&ood_constraints)));
+    coin.reseed(Rpo256::hash_elements(&ood_constraints));
+
+    // FRI layer commitments, and the remainder as both coefficients and evaluations
+    let remainder = fri_proof.parse_remainder::<QuadExt>().unwrap();
+    let remainder_evaluations = fft::evaluate_poly_with_offset(
+        &remainder,
+        &fft::get_twiddles(remainder.len()),
+        Felt::GENERATOR,
+        air.options().blowup_factor(),
+    );
+    tape.extend(
+        fri_roots
+            .iter()
+            .flat_map(|root| to_ints(root.as_elements())),
+    );
+    tape.extend(to_ints(QuadExt::slice_as_base_elements(&remainder)));
+    tape.extend(to_ints(QuadExt::slice_as_base_elements(
+        &remainder_evaluations,
+    )));
+    air.get_deep_composition_coefficients::<QuadExt, _>(&mut coin)
+        .unwrap();
+    for root in &fri_roots {
+        coin.reseed(*root);
+        let _alpha: QuadExt = coin.draw().unwrap();
+    }
+
+    // Query positions, drawn after the proof of work
+    tape.push(pow_nonce);
+    coin.reseed_with_int(pow_nonce);
+    let positions = coin.draw_integers(num_queries, lde_domain_size).unwrap();
+
+    // Queried rows go in the advice map keyed by their hash, and the paths to them in
+    // the Merkle store
+    let mut store = MerkleStore::new();
+    let mut map = Vec::new();
+
+    let main_width = air.trace_layout().main_trace_width();
+    let (main_proof, main_rows) = trace_queries
+        .remove(0)
+        .parse::<Rpo256, Felt>(lde_domain_size, num_queries, main_width)
+        .unwrap();
+    add_queries(
+        &mut store,
+        &mut map,
+        &positions,
+        rows(&main_rows),
+        main_proof,
+    );
+
+    let aux_width = air.trace_layout().get_aux_segment_width(0);
+    let (aux_proof, aux_rows) = trace_queries
+        .remove(0)
+        .parse::<Rpo256, QuadExt>(lde_domain_size, num_queries, aux_width)
+        .unwrap();
+    add_queries(&mut store, &mut map, &positions, rows(&aux_rows), aux_proof);
+
+    let (constraint_proof, constraint_rows) = constraint_queries
+        .parse::<Rpo256, QuadExt>(lde_domain_size, num_queries, air.ce_blowup_factor())
+        .unwrap();
+    add_queries(
+        &mut store,
+        &mut map,
+        &positions,
+        rows(&constraint_rows),
+        constraint_proof,
+    );
+
+    // Each FRI layer is queried at the positions folded down to its domain, where a
+    // leaf holds FOLDING_FACTOR evaluations, padded with a zero word
+    let (layer_queries, layer_proofs) = fri_proof
+        .parse_layers::<Rpo256, QuadExt>(lde_domain_size, fri_options.folding_factor())
+        .unwrap();
+    let mut positions = positions;
+    let mut domain_size = lde_domain_size;
+    for (queries, proof) in layer_queries
+        .into_iter()
+        .zip(layer_proofs)
+        .take(fri_roots.len() - 1)
+    {
+        let folded = fold_positions(&positions, domain_size, FOLDING_FACTOR);
+        let rows = queries
+            .chunks(FOLDING_FACTOR)
+            .map(|evaluations| {
+                let mut row = QuadExt::slice_as_base_elements(evaluations).to_vec();
+                row.extend([Felt::ZERO; 4]);
+                row
+            })
+            .collect();
+        add_queries(&mut store, &mut map, &folded, rows, proof);
+
+        positions = folded;
+        domain_size /= FOLDING_FACTOR;
+    }
+
+    let advice = AdviceInputs::default()
+        .with_stack_values(tape)
+        .unwrap()
+        .with_merkle_store(store)
+        .with_map(map);
+
+    (StackInputs::try_from_values(stack).unwrap(), advice)
+}
+
+/// Splits the batch `proof` of `rows` at `positions` into a path for each row, whose
+/// leaf is the hash of the row.
+fn add_queries(
+    store: &mut MerkleStore,
+    map: &mut Vec<([u8; 32], Vec<Felt>)>,
+    positions: &[usize],
+    rows: Vec<Vec<Felt>>,
+    proof: BatchMerkleProof<Rpo256>,
+) {
+    let paths = proof.into_paths(positions).unwrap();
+    let depth = paths[0].len() as u8 - 1;
+
+    let mut path_set = Vec::new();
+    for ((position, mut path), row) in positions.iter().zip(paths).zip(rows) {
+        let leaf: RpoDigest = path.remove(0);
+        map.push((leaf.as_bytes(), row));
+        path_set.push((*position as u64, leaf, MerklePath::from(path)));
+    }
+
+    let path_set = MerklePathSet::new(depth).with_paths(path_set).unwrap();
+    store.add_merkle_path_set(&path_set).unwrap();
+}
+
+fn rows<E: FieldElement<BaseField = Felt>>(table: &Table<E>) -> Vec<Vec<Felt>> {
+    table
+        .rows()
+        .map(|row| E::slice_as_base_elements(row).to_vec())
+        .collect()
+}
+
+fn to_ints(elements: &[Felt]) -> Vec<u64> {
+    elements.iter().map(Felt::as_int).collect()
+}
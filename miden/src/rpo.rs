@@ -1,42 +1,138 @@
-use miden::{Assembler, ProofOptions};
-use miden_processor::{MemAdviceProvider, StackInputs, VmStateIterator};
+use miden::ProofOptions;
+use miden_crypto::{hash::rpo::Rpo256, Felt, StarkField};
+use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, StackOutputs};
 use miden_prover::ExecutionProof;
+use shared::input::{Hash, HashInput, Visibility};
 
-pub fn rpo(n_bytes: usize) -> (impl Fn() -> ExecutionProof, VmStateIterator) {
-    // We can pack 7 bytes into each field element and hash 4 field elements at a time.
-    let hmerges = f64::ceil(n_bytes as f64 / 4. / f64::floor(63. / 8.));
+use crate::{
+    program::{self, ProgramStats},
+    trace::TraceShape,
+    word_on_stack,
+};
+
+/// Bytes packed into each field element, as 8 would overflow the field
+const ELEMENT_BYTES: usize = 7;
+
+/// The message is private and piped from the advice stack into memory, absorbing
+/// two words (8 elements of 7 bytes) per permutation.
+pub fn input(n_bytes: usize) -> HashInput {
+    HashInput::new(Hash::Rpo256, n_bytes, Visibility::Private)
+        .with_compressions(n_elements(n_bytes).div_ceil(8))
+}
+
+/// `bytes` packed into field elements, zero padded to whole words
+fn pack(bytes: &[u8]) -> Vec<Felt> {
+    let mut elements = bytes
+        .chunks(ELEMENT_BYTES)
+        .map(|chunk| {
+            let mut element = [0; 8];
+            element[..chunk.len()].copy_from_slice(chunk);
+            Felt::new(u64::from_le_bytes(element))
+        })
+        .collect::<Vec<_>>();
+    elements.resize(n_elements(bytes.len()), Felt::new(0));
+    elements
+}
+
+/// Number of elements a packed `n_bytes` message takes up
+fn n_elements(n_bytes: usize) -> usize {
+    n_bytes.div_ceil(ELEMENT_BYTES).next_multiple_of(4)
+}
+
+/// The digest at the top of the stack after `rpo(bytes)`
+pub fn expected(bytes: &[u8]) -> Vec<u64> {
+    word_on_stack(*Rpo256::hash_elements(&pack(bytes)))
+}
+
+pub fn rpo(
+    bytes: &[u8],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let elements = pack(bytes);
+    let num_words = elements.len() / 4;
     let code = format!(
         r#"
+        use.std::mem
+
         begin
-            push.0.0.0.0
-            repeat.{hmerges}
-                push.1.1.1.1
-                hmerge
-            end
+            push.0.{num_words}
+            exec.mem::pipe_words_to_memory
         end
     "#
     );
 
-    let assembler = Assembler::default();
-    let program = assembler.compile(code).unwrap();
-    let vm: miden_processor::VmStateIterator = miden_processor::execute_iter(
-        &program,
-        StackInputs::default(),
-        MemAdviceProvider::default(),
+    let (program, stats) = program::compile(&code);
+
+    let advice_provider = MemAdviceProvider::from(
+        AdviceInputs::default()
+            .with_stack_values(elements.iter().map(|element| element.as_int()))
+            .unwrap(),
     );
+    let shape = TraceShape::measure(&program, StackInputs::default(), advice_provider.clone());
 
     (
         move || {
-            let (_stack, proof) = miden_prover::prove(
+            miden_prover::prove(
                 &program,
                 StackInputs::default(),
-                MemAdviceProvider::default(),
-                ProofOptions::default(),
+                advice_provider.clone(),
+                options.clone(),
             )
-            .unwrap();
+            .unwrap()
+        },
+        shape,
+        stats,
+    )
+}
+
+/// Like `rpo`, but the number of words is a public input rather than a constant, so
+/// the same program hashes every length.
+pub fn rpo_while(
+    bytes: &[u8],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let elements = pack(bytes);
+    let num_words = elements.len() / 4;
+    let code = r#"
+        use.std::mem
+
+        begin
+            # => [num_words, ...]
+            push.0
+            swap
+            exec.mem::pipe_words_to_memory
+        end
+    "#;
+
+    let (program, stats) = program::compile(code);
 
-            proof
+    let stack_inputs = StackInputs::try_from_values([num_words as u64]).unwrap();
+    let advice_provider = MemAdviceProvider::from(
+        AdviceInputs::default()
+            .with_stack_values(elements.iter().map(|element| element.as_int()))
+            .unwrap(),
+    );
+    let shape = TraceShape::measure(&program, stack_inputs.clone(), advice_provider.clone());
+
+    (
+        move || {
+            miden_prover::prove(
+                &program,
+                stack_inputs.clone(),
+                advice_provider.clone(),
+                options.clone(),
+            )
+            .unwrap()
         },
-        vm,
+        shape,
+        stats,
     )
 }
@@ -1,47 +1,319 @@
-use miden::{Assembler, ProofOptions};
-use miden_processor::{MemAdviceProvider, StackInputs, VmStateIterator};
+use std::fmt::Write;
+
+use miden::{Program, ProofOptions};
+use miden_processor::{AdviceInputs, MemAdviceProvider, StackInputs, StackOutputs};
 use miden_prover::ExecutionProof;
+use shared::{
+    input::{sha256_blocks, Hash, HashInput, Visibility},
+    reference,
+};
+
+use crate::{
+    message::{advice_stack, push_block, MessageWord},
+    program::{self, ProgramStats},
+    trace::TraceShape,
+};
+
+/// The message is private, read from the advice stack and padded by the program.
+pub fn input(n_bytes: usize) -> HashInput {
+    HashInput::new(Hash::Sha256, n_bytes, Visibility::Private)
+        .with_compressions(sha256_blocks(n_bytes))
+}
+
+/// The digest words at the top of the stack after `sha(bytes)`
+pub fn expected(bytes: &[u8]) -> Vec<u64> {
+    reference::sha256(bytes)
+        .chunks(4)
+        .map(|word| u32::from_be_bytes(word.try_into().unwrap()).into())
+        .collect()
+}
+
+/// Stores the initial hash value, see section 5.3.3 of FIPS 180-4, where `compress`
+/// keeps the state
+const INIT_STATE: &str = "
+    push.0x5be0cd19.0x1f83d9ab.0x9b05688c.0x510e527f
+    mem_storew.1
+    dropw
+    push.0xa54ff53a.0x3c6ef372.0xbb67ae85.0x6a09e667
+    mem_storew.0
+    dropw
+";
+
+/// The message as big endian words, with the last one zero padded
+fn words(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks(4)
+        .map(|chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_be_bytes(word)
+        })
+        .collect()
+}
+
+/// An `n_bytes` message padded to whole blocks, see section 5.1.1 of
+/// https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf
+fn blocks(n_bytes: usize) -> Vec<[MessageWord; 16]> {
+    let n_words = sha256_blocks(n_bytes) * 16;
+    let bit_len = n_bytes as u64 * 8;
+    let words = (0..n_words)
+        .map(|i| match i * 4 {
+            start if start + 4 <= n_bytes => MessageWord::Advice,
+            start if start < n_bytes => {
+                // Big endian, so the message bytes are the high ones
+                let message_bytes = n_bytes - start;
+                MessageWord::Partial {
+                    mask: u32::MAX >> (8 * message_bytes),
+                    pad: 0x80 << (8 * (3 - message_bytes)),
+                }
+            }
+            _ if i == n_words - 2 => MessageWord::Constant((bit_len >> 32) as u32),
+            _ if i == n_words - 1 => MessageWord::Constant(bit_len as u32),
+            start if start == n_bytes => MessageWord::Constant(0x8000_0000),
+            _ => MessageWord::Constant(0),
+        })
+        .collect::<Vec<_>>();
+
+    words
+        .chunks(16)
+        .map(|block| block.try_into().unwrap())
+        .collect()
+}
+
+/// The program hashing `bytes`, and the advice provider with the message
+fn hash_program(bytes: &[u8]) -> (Program, ProgramStats, MemAdviceProvider) {
+    let blocks = blocks(bytes.len());
+    let full_blocks = blocks
+        .iter()
+        .take_while(|block| block.iter().all(|word| *word == MessageWord::Advice))
+        .count();
+
+    let mut hash_blocks = String::new();
+    if full_blocks > 0 {
+        writeln!(
+            hash_blocks,
+            "repeat.{full_blocks}\n{}exec.compress\nend",
+            push_block(&blocks[0])
+        )
+        .unwrap();
+    }
+    for block in &blocks[full_blocks..] {
+        writeln!(hash_blocks, "{}exec.compress", push_block(block)).unwrap();
+    }
 
-pub fn sha(n_bytes: usize) -> (impl Fn() -> ExecutionProof, VmStateIterator) {
-    // Input: 32-bit (4 bytes) value per element, 16 elements per hash. 8 elements will be spent on the previous hash.
-    // Output: 32-byte digest stored in the first 8 elements of the stack.
-    let sha_ops = f64::ceil(n_bytes as f64 / 4. / 8.);
     let code = format!(
         r#"
-        use.std::crypto::hashes::sha256
+        {procedures}
 
         begin
-            push.1.2.3.4.5.6.7.8
-            repeat.{sha_ops}
-                push.9.10.11.12.13.14.15.16
-                exec.sha256::hash_2to1
-            end
+            {INIT_STATE}
+
+            {hash_blocks}
+
+            padw
+            mem_loadw.1
+            padw
+            mem_loadw.0
         end
-    "#
+    "#,
+        procedures = include_str!("./asm/sha256.masm"),
     );
 
-    let assembler = Assembler::default()
-        .with_library(&miden_stdlib::StdLibrary::default())
-        .unwrap();
-    let program = assembler.compile(code).unwrap();
-    let vm: miden_processor::VmStateIterator = miden_processor::execute_iter(
-        &program,
-        StackInputs::default(),
-        MemAdviceProvider::default(),
+    let (program, stats) = program::compile(&code);
+
+    let advice_provider = MemAdviceProvider::from(
+        AdviceInputs::default()
+            .with_stack_values(advice_stack(&blocks, &words(bytes)))
+            .unwrap(),
     );
+    (program, stats, advice_provider)
+}
+
+pub fn sha(
+    bytes: &[u8],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let (program, stats, advice_provider) = hash_program(bytes);
+    let shape = TraceShape::measure(&program, StackInputs::default(), advice_provider.clone());
 
     (
         move || {
-            let (_stack, proof) = miden::prove(
+            miden::prove(
                 &program,
                 StackInputs::default(),
-                MemAdviceProvider::default(),
-                ProofOptions::default(),
+                advice_provider.clone(),
+                options.clone(),
             )
-            .unwrap();
+            .unwrap()
+        },
+        shape,
+        stats,
+    )
+}
+
+/// The program `sha_while` proves, its inputs, and the advice provider with the message
+fn while_program(bytes: &[u8]) -> (Program, ProgramStats, StackInputs, MemAdviceProvider) {
+    // Memory 2 holds the message length and 3 the offset of the tail word being read
+    let code = format!(
+        r#"
+        {procedures}
 
-            proof
+        # Pushes the tail word at offset mem[3] from the advice stack, checks that it's
+        # the message then 0x80 and zeros, and moves the offset to the previous word
+        proc.tail_word
+            adv_push.1 u32assert
+            # => [word, ...]
+            mem_load.3 mem_load.2 u32checked_gte
+            if.true
+                dup mem_load.3 mem_load.2 eq mul.2147483648 assert_eq # 0x80000000
+            else
+                mem_load.3 add.4 mem_load.2 u32checked_gt
+                if.true
+                    # The message bytes are the high ones, then 0x80 and zeros
+                    dup
+                    push.0xffffffff mem_load.2 mem_load.3 sub mul.8 u32checked_shr
+                    u32checked_and
+                    push.0x80 push.3 mem_load.2 mem_load.3 sub sub mul.8 u32checked_shl
+                    assert_eq
+                end
+            end
+            mem_load.3 sub.4 mem_store.3
+        end
+
+        begin
+            {INIT_STATE}
+
+            # => [n_bytes, ...]
+            dup
+            mem_store.2
+            u32checked_div.64
+            dup
+            neq.0
+            while.true
+                {push_block}
+                exec.compress
+                sub.1
+                dup
+                neq.0
+            end
+            drop
+
+            # Without room for the length after the 0x80, the tail takes two blocks
+            mem_load.2 u32checked_mod.64 push.56 u32checked_gte
+            if.true
+                mem_load.2 u32checked_div.64 mul.64 add.60 mem_store.3
+                repeat.16 exec.tail_word end
+                exec.compress
+            end
+
+            # The last block, ending with the length in bits as a big endian u64, the
+            # high half of which is zero for any message that fits in memory
+            mem_load.2 mul.8
+            push.0
+            mem_load.2 add.8 u32checked_div.64 mul.64 add.52 mem_store.3
+            repeat.14 exec.tail_word end
+            exec.compress
+
+            padw
+            mem_loadw.1
+            padw
+            mem_loadw.0
+        end
+    "#,
+        procedures = include_str!("./asm/sha256.masm"),
+        push_block = push_block(&[MessageWord::Advice; 16]),
+    );
+
+    let (program, stats) = program::compile(&code);
+
+    // Every word but the length is read from the advice stack, padding included
+    let n_blocks = sha256_blocks(bytes.len());
+    let mut blocks = vec![[MessageWord::Advice; 16]; n_blocks];
+    blocks[n_blocks - 1][14..].fill(MessageWord::Constant(0));
+    let mut padded = bytes.to_vec();
+    padded.push(0x80);
+    padded.resize(n_blocks * 64 - 8, 0);
+
+    let stack_inputs = StackInputs::try_from_values([bytes.len() as u64]).unwrap();
+    let advice_provider = MemAdviceProvider::from(
+        AdviceInputs::default()
+            .with_stack_values(advice_stack(&blocks, &words(&padded)))
+            .unwrap(),
+    );
+    (program, stats, stack_inputs, advice_provider)
+}
+
+/// Like `sha`, but the message length is a public input and the blocks are compressed
+/// in a loop at runtime, so the same program hashes every length. The tail of the
+/// message is read with its padding, a word at a time, and each word is checked
+/// against where the message ends.
+pub fn sha_while(
+    bytes: &[u8],
+    options: ProofOptions,
+) -> (
+    impl Fn() -> (StackOutputs, ExecutionProof),
+    TraceShape,
+    ProgramStats,
+) {
+    let (program, stats, stack_inputs, advice_provider) = while_program(bytes);
+    let shape = TraceShape::measure(&program, stack_inputs.clone(), advice_provider.clone());
+
+    (
+        move || {
+            miden::prove(
+                &program,
+                stack_inputs.clone(),
+                advice_provider.clone(),
+                options.clone(),
+            )
+            .unwrap()
         },
-        vm,
+        shape,
+        stats,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_match_the_reference() {
+        for n_bytes in [0, 1, 3, 55, 56, 63, 64, 65, 119, 120, 1000] {
+            let bytes = core::iter::repeat_with(|| shared::fastrand::u8(..))
+                .take(n_bytes)
+                .collect::<Vec<_>>();
+            let (program, _, advice_provider) = hash_program(&bytes);
+            let trace = miden_processor::execute(&program, StackInputs::default(), advice_provider)
+                .unwrap();
+
+            let expected = expected(&bytes);
+            assert_eq!(
+                &trace.stack_outputs().stack()[..expected.len()],
+                expected,
+                "digest of {n_bytes} bytes"
+            );
+        }
+    }
+    #[test]
+    fn while_digests_match_the_reference() {
+        for n_bytes in [0, 1, 3, 55, 56, 63, 64, 65, 119, 120, 1000] {
+            let bytes = core::iter::repeat_with(|| shared::fastrand::u8(..))
+                .take(n_bytes)
+                .collect::<Vec<_>>();
+            let (program, _, stack_inputs, advice_provider) = while_program(&bytes);
+            let trace = miden_processor::execute(&program, stack_inputs, advice_provider).unwrap();
+
+            let expected = expected(&bytes);
+            assert_eq!(
+                &trace.stack_outputs().stack()[..expected.len()],
+                expected,
+                "digest of {n_bytes} bytes"
+            );
+        }
+    }
+}
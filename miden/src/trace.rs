@@ -0,0 +1,51 @@
+use miden::Program;
+use miden_processor::{AdviceProvider, ExecutionTrace, Process, StackInputs};
+
+/// Number of rows each part of the execution trace needs. The prover pads every
+/// part to the same power of two length, so it's `padded_len`, rather than the
+/// number of cycles, that proving time follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceShape {
+    /// Cycles executed, i.e. rows of the system, decoder and stack traces
+    pub main_len: usize,
+    pub range_checker_len: usize,
+    pub hasher_len: usize,
+    pub bitwise_len: usize,
+    pub memory_len: usize,
+    /// All chiplets, including the kernel ROM and a mandatory padding row
+    pub chiplets_len: usize,
+    /// Length of the trace that is actually proven
+    pub padded_len: usize,
+}
+
+impl TraceShape {
+    /// Executes `program` once (without building the trace) to measure it.
+    pub fn measure<A: AdviceProvider>(
+        program: &Program,
+        stack_inputs: StackInputs,
+        advice_provider: A,
+    ) -> Self {
+        let mut process = Process::new(program.kernel().clone(), stack_inputs, advice_provider);
+        process.execute(program).unwrap();
+        let (system, _, _, mut range, chiplets, _) = process.into_parts();
+
+        // Same as the processor does when it builds the trace
+        chiplets.append_range_checks(&mut range);
+        let main_len = system.clk() as usize;
+        let range_checker_len = range.build_8bit_lookup().len;
+        let chiplets_len = chiplets.trace_len();
+        let padded_len = (main_len.max(range_checker_len).max(chiplets_len)
+            + ExecutionTrace::NUM_RAND_ROWS)
+            .next_power_of_two();
+
+        Self {
+            main_len,
+            range_checker_len,
+            hasher_len: chiplets.bitwise_start(),
+            bitwise_len: chiplets.memory_start() - chiplets.bitwise_start(),
+            memory_len: chiplets.kernel_rom_start() - chiplets.memory_start(),
+            chiplets_len,
+            padded_len,
+        }
+    }
+}
@@ -1,9 +1,15 @@
 extern crate noir;
 extern crate rand;
 
+use acvm::FieldElement;
 use benchy::{benchmark, BenchmarkRun};
 use noir::{backends::FIELD_BITS, InputMap, InputValue, Proof};
 use rand::Rng;
+use shared::{
+    input::{keccak256_blocks, sha256_blocks, Hash, HashInput, Visibility},
+    reference, report,
+    security::Security,
+};
 
 #[benchmark]
 fn assert(b: &mut BenchmarkRun) {
@@ -16,7 +22,8 @@ fn assert(b: &mut BenchmarkRun) {
     inputs.insert("y".to_string(), InputValue::Field((2_u128).into()));
 
     let proof = Proof::new(&backend, "assert", dir.join("pkgs/assert"));
-    let proof_bytes = b.run(|| proof.run_and_prove(&inputs));
+    let (proof_bytes, _) = b.run(|| proof.run_and_prove(&inputs));
+    report::record("noir", "assert", &b.name, "security", &security());
     b.log("proof_size_bytes", proof_bytes.len());
     b.log(
         "compressed_proof_size_bytes",
@@ -44,7 +51,12 @@ fn fibonacci(b: &mut BenchmarkRun, p: usize) {
     inputs.insert("b_start".to_string(), InputValue::Field((1_u128).into()));
 
     let proof = Proof::new(&backend, "fib", dir.join(format!("pkgs/fib/{}", p)));
-    let proof_bytes = b.run(|| proof.run_and_prove(&inputs));
+    let (proof_bytes, output) = b.run(|| proof.run_and_prove(&inputs));
+    report::record("noir", "Fibonacci", &b.name, "security", &security());
+    check(
+        output,
+        InputValue::Field(reference::fib(p, FieldElement::zero(), FieldElement::one())),
+    );
     b.log("proof_size_bytes", proof_bytes.len());
     b.log(
         "compressed_proof_size_bytes",
@@ -59,24 +71,29 @@ fn merkle_membership(b: &mut BenchmarkRun) {
 
     let mut inputs = InputMap::new();
 
-    let path = generate_random_u8_slice(320)
-        .iter()
-        .map(|b| InputValue::Field((*b as u128).into()))
-        .collect::<Vec<_>>();
-    let hash = generate_random_u8_slice(32)
-        .iter()
-        .map(|b| InputValue::Field((*b as u128).into()))
-        .collect::<Vec<_>>();
+    let path = generate_random_u8_slice(320);
+    let hash = generate_random_u8_slice(32);
+    let expected = path.chunks(32).fold(hash.clone(), |hash, sibling| {
+        reference::sha256(&[&hash[..], sibling].concat()).to_vec()
+    });
 
-    inputs.insert("hash".to_string(), InputValue::Vec(hash));
-    inputs.insert("path".to_string(), InputValue::Vec(path));
+    inputs.insert("hash".to_string(), bytes_input(&hash));
+    inputs.insert("path".to_string(), bytes_input(&path));
 
     let proof = Proof::new(
         &backend,
         "merkle_membership",
         dir.join("pkgs/merkle_membership"),
     );
-    let proof_bytes = b.run(|| proof.run_and_prove(&inputs));
+    let (proof_bytes, output) = b.run(|| proof.run_and_prove(&inputs));
+    report::record(
+        "noir",
+        "Merkle Membership",
+        &b.name,
+        "security",
+        &security(),
+    );
+    check(output, bytes_input(&expected));
     b.log("proof_size_bytes", proof_bytes.len());
     b.log(
         "compressed_proof_size_bytes",
@@ -90,21 +107,66 @@ fn merkle_membership(b: &mut BenchmarkRun) {
     // ("100k bytes", 100000),
 ])]
 fn sha256(b: &mut BenchmarkRun, p: usize) {
+    report::record(
+        "noir",
+        "SHA256",
+        &b.name,
+        "input",
+        &HashInput::new(Hash::Sha256, p, Visibility::Private).with_compressions(sha256_blocks(p)),
+    );
+
     let backend = noir::backends::ConcreteBackend::default();
     let dir = std::env::current_dir().expect("current dir to exist");
 
     let mut inputs = InputMap::new();
 
     // Generate random bytes
-    let bytes = generate_random_u8_slice(p)
-        .iter()
-        .map(|b| InputValue::Field((*b as u128).into()))
-        .collect::<Vec<_>>();
+    let bytes = generate_random_u8_slice(p);
 
-    inputs.insert("x".to_string(), InputValue::Vec(bytes));
+    inputs.insert("x".to_string(), bytes_input(&bytes));
 
     let proof = Proof::new(&backend, "sha256", dir.join(format!("pkgs/sha256/{}", p)));
-    let proof_bytes = b.run(|| proof.run_and_prove(&inputs));
+    let (proof_bytes, output) = b.run(|| proof.run_and_prove(&inputs));
+    report::record("noir", "SHA256", &b.name, "security", &security());
+    check(output, bytes_input(&reference::sha256(&bytes)));
+    b.log("proof_size_bytes", proof_bytes.len());
+    b.log(
+        "compressed_proof_size_bytes",
+        zstd::encode_all(&proof_bytes[..], 21).unwrap().len(),
+    );
+}
+
+#[benchmark("Keccak256", [
+    ("1k bytes", 1000),
+    ("10k bytes", 10000),
+])]
+fn keccak256(b: &mut BenchmarkRun, p: usize) {
+    report::record(
+        "noir",
+        "Keccak256",
+        &b.name,
+        "input",
+        &HashInput::new(Hash::Keccak256, p, Visibility::Private)
+            .with_compressions(keccak256_blocks(p)),
+    );
+
+    let backend = noir::backends::ConcreteBackend::default();
+    let dir = std::env::current_dir().expect("current dir to exist");
+
+    let mut inputs = InputMap::new();
+
+    let bytes = generate_random_u8_slice(p);
+
+    inputs.insert("x".to_string(), bytes_input(&bytes));
+
+    let proof = Proof::new(
+        &backend,
+        "keccak256",
+        dir.join(format!("pkgs/keccak256/{}", p)),
+    );
+    let (proof_bytes, output) = b.run(|| proof.run_and_prove(&inputs));
+    report::record("noir", "Keccak256", &b.name, "security", &security());
+    check(output, bytes_input(&reference::keccak256(&bytes)));
     b.log("proof_size_bytes", proof_bytes.len());
     b.log(
         "compressed_proof_size_bytes",
@@ -121,6 +183,15 @@ fn pedersen(b: &mut BenchmarkRun, n_bytes: usize) {
     let bytes_per_field = (FIELD_BITS as f64 / 8.).floor();
     let n_fields = (n_bytes as f64 / bytes_per_field).ceil() as usize;
 
+    // Each field element only carries a single random byte
+    report::record(
+        "noir",
+        "pedersen",
+        &b.name,
+        "input",
+        &HashInput::new(Hash::Pedersen, n_fields, Visibility::Private),
+    );
+
     let backend = noir::backends::ConcreteBackend::default();
     let dir = std::env::current_dir().expect("current dir to exist");
 
@@ -139,7 +210,8 @@ fn pedersen(b: &mut BenchmarkRun, n_bytes: usize) {
         "pedersen",
         dir.join(format!("pkgs/pedersen/{}", n_fields)),
     );
-    let proof_bytes = b.run(|| proof.run_and_prove(&inputs));
+    let (proof_bytes, _) = b.run(|| proof.run_and_prove(&inputs));
+    report::record("noir", "pedersen", &b.name, "security", &security());
     b.log("proof_size_bytes", proof_bytes.len());
     b.log(
         "compressed_proof_size_bytes",
@@ -147,6 +219,29 @@ fn pedersen(b: &mut BenchmarkRun, n_bytes: usize) {
     );
 }
 
+/// Barretenberg's UltraPlonk proofs are over BN254, whose discrete log is estimated
+/// at around 100 bits since the exTNFS attacks
+fn security() -> Security {
+    Security::discrete_log(100)
+}
+
+fn bytes_input(bytes: &[u8]) -> InputValue {
+    InputValue::Vec(
+        bytes
+            .iter()
+            .map(|b| InputValue::Field((*b as u128).into()))
+            .collect(),
+    )
+}
+
+fn check(output: Option<InputValue>, expected: InputValue) {
+    assert_eq!(
+        output,
+        Some(expected),
+        "circuit output doesn't match the reference"
+    );
+}
+
 fn generate_random_u8_slice(len: usize) -> Vec<u8> {
     let mut rng = rand::thread_rng();
     let mut vec = Vec::with_capacity(len);
@@ -156,11 +251,16 @@ fn generate_random_u8_slice(len: usize) -> Vec<u8> {
     vec
 }
 
-benchy::main!(
-    "noir",
-    assert,
-    fibonacci,
-    sha256,
-    pedersen,
-    merkle_membership
-);
+fn main() {
+    report::reset("noir");
+    benchy::main!(
+        "noir",
+        assert,
+        fibonacci,
+        sha256,
+        keccak256,
+        pedersen,
+        merkle_membership
+    );
+    main();
+}
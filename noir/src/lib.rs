@@ -70,7 +70,8 @@ impl<'a, B: Backend> Proof<'a, B> {
         // return_value,
     }
 
-    pub fn run_and_prove(self, inputs_map: &InputMap) -> Vec<u8> {
+    /// Returns the proof and the circuit's return value
+    pub fn run_and_prove(self, inputs_map: &InputMap) -> (Vec<u8>, Option<InputValue>) {
         let Proof {
             backend,
             common_reference_string,
@@ -86,13 +87,12 @@ impl<'a, B: Backend> Proof<'a, B> {
         let witness = execute_circuit(backend, circuit.clone(), initial_witness, true)
             .expect("solved witness");
 
-        // Write public inputs into Verifier.toml
-        // let public_abi = abi.clone().public_abi();
-        // let (_, return_value) = public_abi
-        //     .decode(&solved_witness)
-        //     .expect("unable to decode public abi");
+        let (_, return_value) = abi
+            .public_abi()
+            .decode(&witness)
+            .expect("unable to decode public abi");
 
-        backend
+        let proof = backend
             .prove_with_pk(
                 &common_reference_string,
                 &circuit,
@@ -100,7 +100,9 @@ impl<'a, B: Backend> Proof<'a, B> {
                 &proving_key,
                 false,
             )
-            .expect("proof to be generated")
+            .expect("proof to be generated");
+
+        (proof, return_value)
     }
 }
 
@@ -1,6 +1,17 @@
+//! Unlike the other frameworks, Polylang's outputs aren't checked against
+//! `shared::reference`. The programs return nothing, and the hash programs hash
+//! arrays of constant zeros (recorded as `Visibility::Constant`) and discard the
+//! digest, so there's no output to compare until they take the message as input
+//! and return what they compute.
+
 use benchy::{benchmark, BenchmarkRun};
 use polylang_bench::compile;
 use polylang_prover::RunOutput;
+use shared::{
+    input::{Hash, HashInput, Visibility},
+    report,
+    security::Security,
+};
 
 #[benchmark]
 fn assert(b: &mut BenchmarkRun) {
@@ -18,7 +29,7 @@ fn assert(b: &mut BenchmarkRun) {
     );
 
     let output = b.run(run_and_prove);
-    report(b, output);
+    report(b, "assert", output);
 }
 
 #[benchmark("Fibonacci", [
@@ -48,7 +59,7 @@ fn fibonacci(b: &mut BenchmarkRun, p: usize) {
     ));
 
     let output = b.run(run_and_prove);
-    report(b, output);
+    report(b, "Fibonacci", output);
 }
 
 #[benchmark("SHA256", [
@@ -59,6 +70,14 @@ fn fibonacci(b: &mut BenchmarkRun, p: usize) {
 fn sha256(b: &mut BenchmarkRun, p: usize) {
     let bytes_per_element = 4.;
     let arr_size = f64::ceil(p as f64 / bytes_per_element) as usize;
+    report::record(
+        "polylang",
+        "SHA256",
+        &b.name,
+        "input",
+        &HashInput::new(Hash::Sha256, arr_size * 4, Visibility::Constant),
+    );
+
     let run_and_prove = compile(&format!(
         r#"
         function main() {{
@@ -70,7 +89,7 @@ fn sha256(b: &mut BenchmarkRun, p: usize) {
     ));
 
     let output = b.run(run_and_prove);
-    report(b, output);
+    report(b, "SHA256", output);
 }
 
 #[benchmark("Blake3", [
@@ -80,6 +99,14 @@ fn sha256(b: &mut BenchmarkRun, p: usize) {
 fn blake3(b: &mut BenchmarkRun, p: usize) {
     let bytes_per_element = 4.;
     let arr_size = f64::ceil(p as f64 / bytes_per_element) as usize;
+    report::record(
+        "polylang",
+        "Blake3",
+        &b.name,
+        "input",
+        &HashInput::new(Hash::Blake3, arr_size * 4, Visibility::Constant),
+    );
+
     let run_and_prove = compile(&format!(
         r#"
         function main() {{
@@ -91,7 +118,7 @@ fn blake3(b: &mut BenchmarkRun, p: usize) {
     ));
 
     let output = b.run(run_and_prove);
-    report(b, output);
+    report(b, "Blake3", output);
 }
 
 #[benchmark("RPO", [
@@ -101,6 +128,14 @@ fn blake3(b: &mut BenchmarkRun, p: usize) {
 fn rpo(b: &mut BenchmarkRun, p: usize) {
     let bytes_per_element = 4.;
     let arr_size = f64::ceil(p as f64 / bytes_per_element) as usize;
+    report::record(
+        "polylang",
+        "RPO",
+        &b.name,
+        "input",
+        &HashInput::new(Hash::Rpo256, arr_size * 4, Visibility::Constant),
+    );
+
     let run_and_prove = compile(&format!(
         r#"
         function main() {{
@@ -112,10 +147,21 @@ fn rpo(b: &mut BenchmarkRun, p: usize) {
     ));
 
     let output = b.run(run_and_prove);
-    report(b, output);
+    report(b, "RPO", output);
 }
 
-fn report(b: &mut BenchmarkRun, (run_output, proof): (RunOutput, Vec<u8>)) {
+fn report(
+    b: &mut BenchmarkRun,
+    benchmark: &str,
+    (run_output, proof, security_bits): (RunOutput, Vec<u8>, u32),
+) {
+    report::record(
+        "polylang",
+        benchmark,
+        &b.name,
+        "security",
+        &Security::fri(security_bits),
+    );
     b.log("cycles", run_output.cycle_count as usize);
     b.log("proof_size_bytes", proof.len());
 
@@ -123,4 +169,8 @@ fn report(b: &mut BenchmarkRun, (run_output, proof): (RunOutput, Vec<u8>)) {
     b.log("compressed_proof_size_bytes", compressed_proof.len());
 }
 
-benchy::main!("polylang", assert, fibonacci, sha256, blake3, rpo);
+fn main() {
+    report::reset("polylang");
+    benchy::main!("polylang", assert, fibonacci, sha256, blake3, rpo);
+    main();
+}
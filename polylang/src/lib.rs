@@ -2,7 +2,9 @@ use std::collections::HashMap;
 
 use polylang_prover::RunOutput;
 
-pub fn compile(code: &str) -> impl FnOnce() -> (RunOutput, Vec<u8>) {
+/// The returned closure runs and proves the program, returning the proof and its
+/// conjectured security level in bits
+pub fn compile(code: &str) -> impl FnOnce() -> (RunOutput, Vec<u8>, u32) {
     let program = polylang::parse_program(code).unwrap();
     let (miden_code, mut abi) = polylang::compiler::compile(program, None, "main").unwrap();
     let program = polylang_prover::compile_program(&abi, &miden_code).unwrap();
@@ -33,6 +35,6 @@ pub fn compile(code: &str) -> impl FnOnce() -> (RunOutput, Vec<u8>) {
 
         let proof = prove().unwrap();
 
-        (output, proof.to_bytes())
+        (output, proof.to_bytes(), proof.security_level())
     }
 }
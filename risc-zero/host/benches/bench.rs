@@ -1,14 +1,15 @@
 extern crate host;
 
+use std::time::Instant;
+
 use benchy::{benchmark, BenchmarkRun};
-use host::{blake3::blake3, fib::fib, merkle, sha::sha};
+use host::{blake3::blake3, fib::fib, keccak::keccak, merkle, sha::sha, Job, SessionStats};
 use risc0_zkvm::{Receipt, Session};
-use shared::{hash::Sha, tree_size_n, Tree};
+use shared::{hash::Sha, report, tree_size_n, Tree};
 
 #[benchmark]
 fn assert(b: &mut BenchmarkRun) {
-    let prove = host::assert::assert(1, 2);
-    log_session(&b.run(prove), b);
+    run(b, "assert", host::assert::assert(1, 2, None));
 }
 
 #[benchmark("Fibonacci", [
@@ -20,18 +21,37 @@ fn assert(b: &mut BenchmarkRun) {
     ("100000", 100000),
 ])]
 fn fibonacci(b: &mut BenchmarkRun, n: u32) {
-    let prove = fib(n);
-    log_session(&b.run(prove), b);
+    run(b, "Fibonacci", fib(n, None));
+}
+
+// Not the same computation as "Fibonacci": O(log n) u64 matrix multiplications
+#[benchmark("Fibonacci matrix", [
+    ("1", 1),
+    ("10", 10),
+    ("100", 100),
+    ("1000", 1000),
+    ("10000", 10000),
+    ("100000", 100000),
+])]
+fn fibonacci_matrix(b: &mut BenchmarkRun, n: u32) {
+    run(b, "Fibonacci matrix", host::fib::fib_matrix(n, None));
 }
 
 #[benchmark("SHA256", [
-    ("1k bytes", 1),
-    ("10k bytes", 10),
-    // ("100k bytes", 100),
+    ("1k bytes", 1000),
+    ("10k bytes", 10000),
+    // ("100k bytes", 100000),
 ])]
 fn sha256(b: &mut BenchmarkRun, n: usize) {
-    let prove = sha(n);
-    log_session(&b.run(prove), b);
+    report::record(
+        "risc-zero",
+        "SHA256",
+        &b.name,
+        "input",
+        &host::sha::input(n),
+    );
+
+    run(b, "SHA256", sha(&random_bytes(n), None));
 }
 
 #[benchmark("Blake3", [
@@ -40,39 +60,253 @@ fn sha256(b: &mut BenchmarkRun, n: usize) {
     // ("100k bytes", 100),
 ])]
 fn blake3_bench(b: &mut BenchmarkRun, n: usize) {
-    let prove = blake3(n);
-    log_session(&b.run(prove), b);
+    report::record(
+        "risc-zero",
+        "Blake3",
+        &b.name,
+        "input",
+        &host::blake3::input(n),
+    );
+
+    run(b, "Blake3", blake3(n, None));
+}
+
+#[benchmark("Keccak256", [
+    ("1k bytes", 1000),
+    ("10k bytes", 10000),
+])]
+fn keccak256(b: &mut BenchmarkRun, n: usize) {
+    report::record(
+        "risc-zero",
+        "Keccak256",
+        &b.name,
+        "input",
+        &host::keccak::input(n),
+    );
+
+    run(b, "Keccak256", keccak(&random_bytes(n), None));
 }
 
 #[benchmark("Merkle Tree Merge", [
     ("1 + 1", (tree_size_n::<Sha>(0), tree_size_n::<Sha>(0))),
-    // ("2^10 + 2^10", (tree_size_n(10), tree_size_n(10))),
-    // ("2^10 + 2^20", (tree_size_n(10), tree_size_n(20))),
-    // ("2^20 + 2^20", (tree_size_n(20), tree_size_n(20))),
+    ("2^10 + 2^10", (tree_size_n(10), tree_size_n(10))),
+    ("2^10 + 2^20", (tree_size_n(10), tree_size_n(20))),
+    ("2^20 + 2^20", (tree_size_n(20), tree_size_n(20))),
 ])]
 fn merkle_merge(b: &mut BenchmarkRun, (tree1, tree2): (Tree<Sha>, Tree<Sha>)) {
-    let prove = merkle::merkle(tree1, tree2);
-    log_session(&b.run(prove), b);
+    run(b, "Merkle Tree Merge", merkle::merkle(tree1, tree2, None));
 }
 
 #[benchmark("Merkle Membership")]
 fn merkle_membership(b: &mut BenchmarkRun) {
-    let prove = merkle::merkle_membership(10);
-    log_session(&b.run(prove), b);
-}
-
-fn log_session((receipt, session): &(Receipt, Session), b: &mut BenchmarkRun) {
-    let segments = session.resolve().unwrap();
-    let (cycles, insn_cycles) = segments
-        .iter()
-        .fold((0, 0), |(cycles, insn_cycles), segment| {
-            (
-                cycles + (1 << segment.po2),
-                insn_cycles + segment.insn_cycles,
-            )
-        });
-    b.log("cycles", cycles);
-    b.log("instruction_cycles", insn_cycles);
+    run(b, "Merkle Membership", merkle::merkle_membership(10, None));
+}
+
+// The same work with and without risc0's bigint accelerator, which multiplies
+// 256-bit integers modulo a third in one call
+#[benchmark("256-bit ModMul", [
+    ("100 accelerated", (100, true)),
+    ("100 software", (100, false)),
+    ("1000 accelerated", (1000, true)),
+    ("1000 software", (1000, false)),
+    ("10000 accelerated", (10000, true)),
+    ("10000 software", (10000, false)),
+])]
+fn modmul(b: &mut BenchmarkRun, (n, accelerated): (u32, bool)) {
+    report::record(
+        "risc-zero",
+        "256-bit ModMul",
+        &b.name,
+        "accelerated",
+        &accelerated,
+    );
+
+    run(
+        b,
+        "256-bit ModMul",
+        host::modmul::modmul(n, accelerated, None),
+    );
+}
+
+#[benchmark("ECDSA secp256k1 verify", [
+    ("accelerated", true),
+    ("software", false),
+])]
+fn ecdsa_verify(b: &mut BenchmarkRun, accelerated: bool) {
+    report::record(
+        "risc-zero",
+        "ECDSA secp256k1 verify",
+        &b.name,
+        "accelerated",
+        &accelerated,
+    );
+
+    run(
+        b,
+        "ECDSA secp256k1 verify",
+        host::ecdsa::ecdsa(32, accelerated, None),
+    );
+}
+
+// Smaller segments are cheaper to prove one at a time, but every segment adds a
+// receipt and padding, so these find where the total cost bottoms out between
+// risc0's minimum segment size of 2^13 cycles and its default of 2^20
+#[benchmark("Fibonacci segment size", [
+    ("2^13", 13),
+    ("2^14", 14),
+    ("2^16", 16),
+    ("2^18", 18),
+    ("2^20", 20),
+])]
+fn fibonacci_segment_size(b: &mut BenchmarkRun, po2: usize) {
+    run(b, "Fibonacci segment size", fib(100000, Some(po2)));
+}
+
+#[benchmark("SHA256 segment size", [
+    ("2^13", 13),
+    ("2^14", 14),
+    ("2^16", 16),
+    ("2^18", 18),
+    ("2^20", 20),
+])]
+fn sha256_segment_size(b: &mut BenchmarkRun, po2: usize) {
+    report::record(
+        "risc-zero",
+        "SHA256 segment size",
+        &b.name,
+        "input",
+        &host::sha::input(10000),
+    );
+
+    run(
+        b,
+        "SHA256 segment size",
+        sha(&random_bytes(10000), Some(po2)),
+    );
+}
+
+#[benchmark("Merkle Tree Merge segment size", [
+    ("2^13", 13),
+    ("2^14", 14),
+    ("2^16", 16),
+    ("2^18", 18),
+    ("2^20", 20),
+])]
+fn merkle_merge_segment_size(b: &mut BenchmarkRun, po2: usize) {
+    run(
+        b,
+        "Merkle Tree Merge segment size",
+        merkle::merkle(tree_size_n(10), tree_size_n(10), Some(po2)),
+    );
+}
+
+// Too slow to prove, but executing it shows how the guest scales
+#[benchmark("SHA256 execution", [
+    ("100k bytes", 100000),
+])]
+fn sha256_execution(b: &mut BenchmarkRun, n: usize) {
+    report::record(
+        "risc-zero",
+        "SHA256 execution",
+        &b.name,
+        "input",
+        &host::sha::input(n),
+    );
+
+    let job = sha(&random_bytes(n), None);
+    record_image_id(b, "SHA256 execution", &job);
+    execute(b, "SHA256 execution", job);
+}
+
+// Proving the biggest merges takes too long to repeat, but executing them shows how
+// the streaming guest scales with the trees
+#[benchmark("Merkle Tree Merge execution", [
+    ("2^10 + 2^10", (tree_size_n::<Sha>(10), tree_size_n::<Sha>(10))),
+    ("2^20 + 2^20", (tree_size_n(20), tree_size_n(20))),
+])]
+fn merkle_merge_execution(b: &mut BenchmarkRun, (tree1, tree2): (Tree<Sha>, Tree<Sha>)) {
+    let job = merkle::merkle(tree1, tree2, None);
+    record_image_id(b, "Merkle Tree Merge execution", &job);
+    execute(b, "Merkle Tree Merge execution", job);
+}
+
+/// With `RISC0_EXECUTE_ONLY=1`, benchmarks only execute their guest, so cycle
+/// counts can be checked in seconds after changing a guest. Profiling slows
+/// execution down, so profiled builds never prove.
+fn execute_only() -> bool {
+    cfg!(feature = "profiler")
+        || std::env::var("RISC0_EXECUTE_ONLY").is_ok_and(|value| value == "1" || value == "true")
+}
+
+fn run(b: &mut BenchmarkRun, benchmark: &str, mut job: Job) {
+    record_image_id(b, benchmark, &job);
+
+    if execute_only() {
+        execute(b, benchmark, job);
+    } else {
+        let proof = b.run(|| job.prove());
+
+        let start = Instant::now();
+        job.verify(&proof.0);
+        b.log("verification_time_us", start.elapsed().as_micros() as usize);
+
+        log_session(&proof, b, benchmark);
+    }
+}
+
+fn execute(b: &mut BenchmarkRun, benchmark: &str, mut job: Job) {
+    report::record("risc-zero", benchmark, &b.name, "executeOnly", &true);
+
+    // The case's time is its proving time, so it's left at zero
+    let start = Instant::now();
+    let session = job.execute();
+    b.log("execution_time_us", start.elapsed().as_micros() as usize);
+    log_stats(b, &SessionStats::new(&session));
+
+    #[cfg(feature = "profiler")]
+    write_profile(b, benchmark, &job);
+}
+
+/// Writes the guest's profile to `profiles/<benchmark>/<case>.pb` in
+/// `BENCHY_OUTPUT_DIR`, next to the results
+#[cfg(feature = "profiler")]
+fn write_profile(b: &BenchmarkRun, benchmark: &str, job: &Job) {
+    let Ok(dir) = std::env::var("BENCHY_OUTPUT_DIR") else {
+        return;
+    };
+    let dir = std::path::Path::new(&dir).join("profiles").join(benchmark);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(format!("{}.pb", b.name)), job.profile()).unwrap();
+}
+
+/// The image ID identifies the guest binary, so results from a changed guest
+/// can be told apart from earlier runs
+fn record_image_id(b: &BenchmarkRun, benchmark: &str, job: &Job) {
+    report::record(
+        "risc-zero",
+        benchmark,
+        &b.name,
+        "imageId",
+        &job.image_id().to_string(),
+    );
+}
+
+fn random_bytes(n: usize) -> Vec<u8> {
+    core::iter::repeat_with(|| shared::fastrand::u8(..))
+        .take(n)
+        .collect()
+}
+
+fn log_session((receipt, session): &(Receipt, Session), b: &mut BenchmarkRun, benchmark: &str) {
+    report::record(
+        "risc-zero",
+        benchmark,
+        &b.name,
+        "security",
+        &host::security(),
+    );
+
+    log_stats(b, &SessionStats::new(session));
 
     let proof = bincode::serialize(receipt).unwrap();
     b.log("proof_size_bytes", proof.len());
@@ -82,12 +316,32 @@ fn log_session((receipt, session): &(Receipt, Session), b: &mut BenchmarkRun) {
     );
 }
 
-benchy::main!(
-    "risc-zero",
-    assert,
-    fibonacci,
-    sha256,
-    blake3_bench,
-    merkle_merge,
-    merkle_membership,
-);
+fn log_stats(b: &mut BenchmarkRun, stats: &SessionStats) {
+    b.log("cycles", stats.cycles);
+    b.log("instruction_cycles", stats.instruction_cycles);
+    b.log("paging_cycles", stats.paging_cycles);
+    b.log("segments", stats.segments);
+}
+
+fn main() {
+    report::reset("risc-zero");
+    benchy::main!(
+        "risc-zero",
+        assert,
+        fibonacci,
+        fibonacci_matrix,
+        sha256,
+        blake3_bench,
+        keccak256,
+        merkle_merge,
+        merkle_membership,
+        modmul,
+        ecdsa_verify,
+        fibonacci_segment_size,
+        sha256_segment_size,
+        merkle_merge_segment_size,
+        sha256_execution,
+        merkle_merge_execution,
+    );
+    main();
+}
@@ -1,15 +1,10 @@
-use methods::ASSERT_ELF;
-use risc0_zkvm::{Executor, ExecutorEnv, Receipt, Session};
+use methods::{ASSERT_ELF, ASSERT_ID};
 
-pub fn assert(a: u32, b: u32) -> impl FnMut() -> (Receipt, Session) {
-    let env = ExecutorEnv::builder().add_input(&[a, b]).build().unwrap();
+use crate::{env_builder, Job};
 
-    let mut exec = Executor::from_elf(env, ASSERT_ELF).unwrap();
+pub fn assert(a: u32, b: u32, segment_limit_po2: Option<usize>) -> Job<'static> {
+    let mut env = env_builder(segment_limit_po2);
+    env.add_input(&[a, b]);
 
-    move || {
-        let session = exec.run().unwrap();
-        let receipt = session.prove().unwrap();
-
-        (receipt, session)
-    }
+    Job::new(&mut env, ASSERT_ELF, ASSERT_ID)
 }
@@ -1,17 +1,21 @@
-use methods::BLAKE3_ELF;
-use risc0_zkvm::{Executor, ExecutorEnv, Receipt, Session};
+use methods::{BLAKE3_ELF, BLAKE3_ID};
+use shared::{
+    input::{blake3_blocks, Hash, HashInput, Visibility},
+    reference,
+};
 
-pub fn blake3(n_thousands: usize) -> impl FnMut() -> (Receipt, Session) {
-    let env = ExecutorEnv::builder()
-        .add_input(&[n_thousands])
-        .build()
-        .unwrap();
+use crate::{env_builder, Job};
 
-    let mut exec = Executor::from_elf(env, BLAKE3_ELF).unwrap();
+/// The guest hashes the same constant 1000 byte array `n_thousands` times, and
+/// commits the digest
+pub fn input(n_thousands: usize) -> HashInput {
+    HashInput::new(Hash::Blake3, n_thousands * 1000, Visibility::Constant)
+        .with_compressions(n_thousands * blake3_blocks(1000))
+}
+
+pub fn blake3(n_thousands: usize, segment_limit_po2: Option<usize>) -> Job<'static> {
+    let mut env = env_builder(segment_limit_po2);
+    env.add_input(&[n_thousands]);
 
-    move || {
-        let session = exec.run().unwrap();
-        let receipt = session.prove().unwrap();
-        (receipt, session)
-    }
+    Job::new(&mut env, BLAKE3_ELF, BLAKE3_ID).expect(reference::blake3(&[123; 1000]))
 }
@@ -0,0 +1,39 @@
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+use methods::{ECDSA_ELF, ECDSA_ID, ECDSA_SOFTWARE_ELF, ECDSA_SOFTWARE_ID};
+use risc0_zkvm::serde::to_vec;
+
+use crate::{env_builder, Job};
+
+/// Verifies a secp256k1 ECDSA signature of a random `message_len` byte message,
+/// with risc0's accelerated k256 if `accelerated`, or the upstream crate otherwise
+pub fn ecdsa(
+    message_len: usize,
+    accelerated: bool,
+    segment_limit_po2: Option<usize>,
+) -> Job<'static> {
+    let key = core::iter::repeat_with(random_bytes::<32>)
+        .find_map(|bytes| SigningKey::from_slice(&bytes).ok())
+        .unwrap();
+    let message = core::iter::repeat_with(|| shared::fastrand::u8(..))
+        .take(message_len)
+        .collect::<Vec<_>>();
+    let signature: Signature = key.sign(&message);
+    let public_key = key.verifying_key().to_encoded_point(true);
+
+    let mut env = env_builder(segment_limit_po2);
+    env.add_input(&to_vec(&(public_key, &message, signature)).unwrap());
+
+    let (elf, image_id) = if accelerated {
+        (ECDSA_ELF, ECDSA_ID)
+    } else {
+        (ECDSA_SOFTWARE_ELF, ECDSA_SOFTWARE_ID)
+    };
+
+    Job::new(&mut env, elf, image_id).expect((public_key, message))
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    bytes.fill_with(|| shared::fastrand::u8(..));
+    bytes
+}
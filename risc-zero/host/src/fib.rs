@@ -1,15 +1,47 @@
-use methods::FIB_ELF;
-use risc0_zkvm::{Executor, ExecutorEnv, Receipt, Session};
+use std::{num::Wrapping, ops::Add};
 
-pub fn fib(n: u32) -> impl FnMut() -> (Receipt, Session) {
-    let env = ExecutorEnv::builder().add_input(&[n]).build().unwrap();
+use methods::{FIB_ELF, FIB_ID, FIB_MATRIX_ELF, FIB_MATRIX_ID};
+use shared::reference;
 
-    let mut exec = Executor::from_elf(env, FIB_ELF).unwrap();
+use crate::{env_builder, Job};
 
-    move || {
-        let session = exec.run().unwrap();
-        let receipt = session.prove().unwrap();
+/// The Goldilocks prime the guest reduces by, as miden does
+const PRIME: u64 = 0xffff_ffff_0000_0001;
 
-        (receipt, session)
+/// An integer modulo [PRIME]
+#[derive(Debug, Clone, Copy)]
+struct Goldilocks(u64);
+
+impl Add for Goldilocks {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(((self.0 as u128 + rhs.0 as u128) % PRIME as u128) as u64)
     }
 }
+
+/// `n` sequential additions modulo a prime, like the other frameworks
+pub fn fib(n: u32, segment_limit_po2: Option<usize>) -> Job<'static> {
+    let mut env = env_builder(segment_limit_po2);
+    env.add_input(&[n]);
+
+    let expected = reference::fib(n as usize, Goldilocks(0), Goldilocks(1)).0;
+
+    Job::new(&mut env, FIB_ELF, FIB_ID).expect(expected)
+}
+
+/// Fibonacci by raising a 2x2 `u64` matrix to the power `n - 1`, which takes
+/// O(log n) multiplications rather than n additions, so it isn't comparable
+/// with the other frameworks
+pub fn fib_matrix(n: u32, segment_limit_po2: Option<usize>) -> Job<'static> {
+    assert!(n > 0, "the matrix power is n - 1");
+
+    let mut env = env_builder(segment_limit_po2);
+    env.add_input(&[n]);
+
+    // The guest's u64 matrix power wraps, and its top left entry is the
+    // (n - 1)th iteration
+    let expected = reference::fib(n as usize - 1, Wrapping(0u64), Wrapping(1)).0;
+
+    Job::new(&mut env, FIB_MATRIX_ELF, FIB_MATRIX_ID).expect(expected)
+}
@@ -0,0 +1,26 @@
+use methods::{KECCAK_ELF, KECCAK_ID};
+use risc0_zkvm::serde::to_vec;
+use shared::{
+    input::{keccak256_blocks, Hash, HashInput, Visibility},
+    reference,
+};
+
+use crate::{env_builder, Job};
+
+/// The guest reads the message from the host and commits its digest
+pub fn input(n_bytes: usize) -> HashInput {
+    HashInput::new(Hash::Keccak256, n_bytes, Visibility::Private)
+        .with_compressions(keccak256_blocks(n_bytes))
+}
+
+pub fn keccak(bytes: &[u8], segment_limit_po2: Option<usize>) -> Job<'static> {
+    // The guest reads the message as words, so the last one is zero padded
+    let mut padded = bytes.to_vec();
+    padded.resize(bytes.len().div_ceil(4) * 4, 0);
+
+    let mut env = env_builder(segment_limit_po2);
+    env.add_input(&to_vec(&(bytes.len() as u32)).unwrap())
+        .add_input(&padded);
+
+    Job::new(&mut env, KECCAK_ELF, KECCAK_ID).expect(reference::keccak256(bytes))
+}
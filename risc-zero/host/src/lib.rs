@@ -1,5 +1,234 @@
+#[cfg(feature = "profiler")]
+use std::{cell::RefCell, rc::Rc};
+use std::{collections::BTreeSet, fmt::Debug};
+
+#[cfg(feature = "profiler")]
+use risc0_zkvm::Profiler;
+
+use risc0_zkvm::{
+    serde::from_slice, sha::Digest, Executor, ExecutorEnv, ExecutorEnvBuilder, MemoryImage,
+    Receipt, Segment, Session, PAGE_SIZE,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use shared::security::Security;
+
 pub mod assert;
 pub mod blake3;
+pub mod ecdsa;
 pub mod fib;
+pub mod keccak;
 pub mod merkle;
+pub mod modmul;
 pub mod sha;
+
+/// risc0 always proves with 50 FRI queries (`risc0_zkp::QUERIES`), which it
+/// documents as ~100 bits of conjectured security. Unlike miden's proof options,
+/// this can't be configured.
+pub fn security() -> Security {
+    Security::fri(100)
+}
+
+/// An executor environment that splits the session into segments of at most
+/// 2^`segment_limit_po2` cycles, or risc0's default of 2^20 cycles if `None`.
+pub(crate) fn env_builder<'a>(segment_limit_po2: Option<usize>) -> ExecutorEnvBuilder<'a> {
+    let mut builder = ExecutorEnv::builder();
+    if let Some(po2) = segment_limit_po2 {
+        builder.segment_limit_po2(po2);
+    }
+    builder
+}
+
+/// Checks a guest's journal
+type Check<'a> = Box<dyn Fn(&[u8]) + 'a>;
+
+/// A guest and its input. Executing it is enough to count cycles, and takes a
+/// fraction of the time proving does.
+///
+/// With the `profiler` feature, every instruction the guest executes is also
+/// attributed to the function it belongs to, see [Job::profile].
+pub struct Job<'a> {
+    exec: Executor<'a>,
+    image_id: Digest,
+    check: Check<'a>,
+    #[cfg(feature = "profiler")]
+    profiler: Rc<RefCell<Profiler>>,
+}
+
+impl<'a> Job<'a> {
+    pub(crate) fn new(env: &mut ExecutorEnvBuilder<'a>, elf: &[u8], image_id: [u32; 8]) -> Self {
+        #[cfg(feature = "profiler")]
+        let profiler = {
+            let profiler = Rc::new(RefCell::new(Profiler::new("guest", elf).unwrap()));
+            let callback = profiler.clone();
+            env.trace_callback(move |event| (callback.borrow_mut().make_trace_callback())(event));
+            profiler
+        };
+
+        Self {
+            exec: Executor::from_elf(env.build().unwrap(), elf).unwrap(),
+            image_id: image_id.into(),
+            check: Box::new(|_| ()),
+            #[cfg(feature = "profiler")]
+            profiler,
+        }
+    }
+
+    /// Checks every execution's journal against the natively computed
+    /// `expected` output, so a wrong guest fails rather than being benchmarked
+    pub(crate) fn expect<T>(mut self, expected: T) -> Self
+    where
+        T: DeserializeOwned + PartialEq + Debug + 'a,
+    {
+        self.check = Box::new(move |journal| {
+            let output: T = from_slice(journal).unwrap();
+            assert_eq!(output, expected, "the guest committed the wrong output");
+        });
+        self
+    }
+
+    /// The guest's image ID, which changes whenever its binary does
+    pub fn image_id(&self) -> Digest {
+        self.image_id
+    }
+
+    pub fn execute(&mut self) -> Session {
+        let session = self.exec.run().unwrap();
+        (self.check)(&session.journal);
+        session
+    }
+
+    pub fn prove(&mut self) -> (Receipt, Session) {
+        let session = self.execute();
+        let receipt = session.prove().unwrap();
+        (receipt, session)
+    }
+
+    /// Panics unless `receipt` proves an execution of this job's guest
+    pub fn verify(&self, receipt: &Receipt) {
+        receipt.verify(self.image_id).unwrap();
+    }
+
+    /// The cycles spent at each location in the guest since the job was created,
+    /// as a pprof protobuf, e.g. for `go tool pprof -top`. Only the innermost
+    /// function is known, along with any inlined into it.
+    #[cfg(feature = "profiler")]
+    pub fn profile(&self) -> Vec<u8> {
+        let mut profiler = self.profiler.borrow_mut();
+        profiler.finalize();
+        profiler.encode_to_vec()
+    }
+}
+
+/// Where the cycles of a session went
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionStats {
+    pub segments: usize,
+    /// Cycles that are proven, i.e. each segment padded to a power of two
+    pub cycles: usize,
+    /// Cycles spent executing the guest's instructions
+    pub instruction_cycles: usize,
+    /// Cycles spent hashing the memory pages a segment reads and writes, to
+    /// check them against the segment's pre and post image
+    pub paging_cycles: usize,
+}
+
+impl SessionStats {
+    pub fn new(session: &Session) -> Self {
+        session
+            .resolve()
+            .unwrap()
+            .iter()
+            .fold(Self::default(), |stats, segment| Self {
+                segments: stats.segments + 1,
+                cycles: stats.cycles + (1 << segment.po2),
+                instruction_cycles: stats.instruction_cycles + segment.insn_cycles,
+                paging_cycles: stats.paging_cycles + paging_cycles(segment),
+            })
+    }
+}
+
+/// A [Segment] up to its page faults, which risc0 doesn't expose other than by
+/// serializing the segment. This follows risc0 0.18's field order, so risc0-zkvm
+/// is pinned to that version in Cargo.toml.
+#[derive(Deserialize)]
+struct SegmentFaults {
+    pre_image: MemoryImage,
+    _post_image_id: Digest,
+    faults: PageFaults,
+}
+
+#[derive(Deserialize)]
+struct PageFaults {
+    reads: BTreeSet<u32>,
+    writes: BTreeSet<u32>,
+}
+
+/// Counted the way risc0's executor does: every page that is read or written is
+/// hashed one SHA-256 block at a time, and the root page only covers the root
+/// entries.
+fn paging_cycles(segment: &Segment) -> usize {
+    const BLOCK_BYTES: usize = 64;
+    const SHA_INIT: usize = 5;
+    const SHA_LOAD: usize = 16;
+    const SHA_MAIN: usize = 52;
+
+    let bytes = bincode::serialize(segment).unwrap();
+    let SegmentFaults {
+        pre_image, faults, ..
+    } = bincode::deserialize(&bytes).unwrap();
+    let info = &pre_image.info;
+
+    faults
+        .reads
+        .iter()
+        .chain(&faults.writes)
+        .map(|&page| {
+            let blocks = if page == info.root_idx {
+                info.num_root_entries as usize / 2
+            } else {
+                PAGE_SIZE / BLOCK_BYTES
+            };
+            1 + SHA_INIT + (SHA_LOAD + SHA_MAIN) * blocks
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paging_and_instructions_fill_each_segment() {
+        const SEGMENT_PO2: usize = 15;
+        // Generous bounds on the cycles risc0 reserves in every segment, to load and
+        // store the image and for zero knowledge, and on what one instruction can add,
+        // including the pages it faults in
+        const RESERVED: usize = 2048;
+        const STEP: usize = 4 * (1 + 5 + (16 + 52) * PAGE_SIZE / 64);
+
+        let session = fib::fib(100000, Some(SEGMENT_PO2)).execute();
+        let segments = session.resolve().unwrap();
+        assert!(segments.len() > 2);
+
+        // The executor only splits once the next instruction won't fit, so every
+        // segment but the last is full
+        let (last, full) = segments.split_last().unwrap();
+        for segment in full {
+            let used = segment.insn_cycles + paging_cycles(segment);
+            assert_eq!(segment.po2, SEGMENT_PO2);
+            assert!(
+                used <= 1 << SEGMENT_PO2,
+                "{used} cycles don't fit in the segment"
+            );
+            assert!(
+                used + RESERVED + STEP > 1 << SEGMENT_PO2,
+                "{used} cycles leave the segment too empty"
+            );
+        }
+
+        let stats = SessionStats::new(&session);
+        assert_eq!(stats.segments, segments.len());
+        assert_eq!(stats.cycles, (full.len() << SEGMENT_PO2) + (1 << last.po2));
+        assert!(stats.instruction_cycles + stats.paging_cycles <= stats.cycles);
+    }
+}
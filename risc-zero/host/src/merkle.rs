@@ -1,44 +1,55 @@
-use methods::{MERKLE_ELF, MERKLE_MEMBERSHIP_ELF};
-use risc0_zkvm::{serde::to_vec, Executor, ExecutorEnv, Receipt, Session};
+use methods::{MERKLE_ELF, MERKLE_ID, MERKLE_MEMBERSHIP_ELF, MERKLE_MEMBERSHIP_ID};
+use risc0_zkvm::{serde::to_vec, sha::Digest};
 use shared::{
     hash::{HashFn, Sha},
-    Tree,
+    reference, Tree,
 };
 
-pub fn merkle(tree1: Tree<Sha>, tree2: Tree<Sha>) -> impl FnMut() -> (Receipt, Session) {
-    let env = ExecutorEnv::builder()
-        .add_input(&to_vec(&tree1).unwrap())
-        .add_input(&to_vec(&tree2).unwrap())
-        .build()
-        .unwrap();
-
-    let mut exec = Executor::from_elf(env, MERKLE_ELF).unwrap();
+use crate::{env_builder, Job};
+
+/// The guest reads each tree as its leaf count followed by its leaves, which it
+/// merges as they are read, so it never holds either tree. Like miden's
+/// `mtree_merge`, the merged root is the hash of the two roots.
+pub fn merkle(
+    tree1: Tree<Sha>,
+    tree2: Tree<Sha>,
+    segment_limit_po2: Option<usize>,
+) -> Job<'static> {
+    let expected = Sha::merge(tree1.digest(), tree2.digest());
+
+    let mut env = env_builder(segment_limit_po2);
+    for tree in [&tree1, &tree2] {
+        let leaves = leaves(tree);
+        env.add_input(&to_vec(&(leaves.len() as u32)).unwrap())
+            .add_input(&leaves);
+    }
 
-    move || {
-        let session = exec.run().unwrap();
-        let receipt = session.prove().unwrap();
+    Job::new(&mut env, MERKLE_ELF, MERKLE_ID).expect(expected)
+}
 
-        (receipt, session)
-    }
+fn leaves(tree: &Tree<Sha>) -> Vec<Digest> {
+    tree.iter()
+        .filter_map(|node| match node {
+            Tree::Leaf(digest) => Some(*digest),
+            Tree::Node { .. } => None,
+        })
+        .collect()
 }
 
-pub fn merkle_membership(path_size: usize) -> impl FnMut() -> (Receipt, Session) {
+pub fn merkle_membership(path_size: usize, segment_limit_po2: Option<usize>) -> Job<'static> {
+    // The leaf, then its siblings from the bottom up
     let path = core::iter::from_fn(|| Some(Sha::random()))
         .take(path_size + 1)
-        .flat_map(|sha| sha.as_bytes().to_vec())
         .collect::<Vec<_>>();
+    let index = shared::fastrand::u64(..)
+        & 1u64
+            .checked_shl(path_size as u32)
+            .map_or(u64::MAX, |m| m - 1);
+    let expected = reference::merkle_path::<Sha>(path[0], index, &path[1..]);
 
-    let env = ExecutorEnv::builder()
-        .add_input(&to_vec(&path).unwrap())
-        .build()
-        .unwrap();
+    let mut env = env_builder(segment_limit_po2);
+    env.add_input(&to_vec(&index).unwrap())
+        .add_input(&to_vec(&path).unwrap());
 
-    let mut exec = Executor::from_elf(env, MERKLE_MEMBERSHIP_ELF).unwrap();
-
-    move || {
-        let session = exec.run().unwrap();
-        let receipt = session.prove().unwrap();
-
-        (receipt, session)
-    }
+    Job::new(&mut env, MERKLE_MEMBERSHIP_ELF, MERKLE_MEMBERSHIP_ID).expect(expected)
 }
@@ -0,0 +1,39 @@
+use methods::{MODMUL_ELF, MODMUL_ID, MODMUL_SOFTWARE_ELF, MODMUL_SOFTWARE_ID};
+use risc0_zkvm::serde::to_vec;
+use shared::reference;
+
+use crate::{env_builder, Job};
+
+/// secp256k1's base field prime, 2^256 - 2^32 - 977, little endian
+pub const MODULUS: [u8; 32] = {
+    let mut bytes = [0xff; 32];
+    bytes[0] = 0x2f;
+    bytes[1] = 0xfc;
+    bytes[4] = 0xfe;
+    bytes
+};
+
+/// `n` multiplications of 256-bit integers modulo [MODULUS], with the bigint
+/// accelerator if `accelerated`, or in software otherwise
+pub fn modmul(n: u32, accelerated: bool, segment_limit_po2: Option<usize>) -> Job<'static> {
+    let (x, y) = (random_element(), random_element());
+
+    let mut env = env_builder(segment_limit_po2);
+    env.add_input(&to_vec(&(x, y, MODULUS, n)).unwrap());
+
+    let (elf, image_id) = if accelerated {
+        (MODMUL_ELF, MODMUL_ID)
+    } else {
+        (MODMUL_SOFTWARE_ELF, MODMUL_SOFTWARE_ID)
+    };
+
+    Job::new(&mut env, elf, image_id).expect(reference::modmul(&x, &y, &MODULUS, n as usize))
+}
+
+/// Below 2^255, and so below [MODULUS]
+fn random_element() -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes.fill_with(|| shared::fastrand::u8(..));
+    bytes[31] &= 0x7f;
+    bytes
+}
@@ -1,18 +1,26 @@
-use methods::SHA_ELF;
-use risc0_zkvm::{Executor, ExecutorEnv, Receipt, Session};
+use methods::{SHA_ELF, SHA_ID};
+use risc0_zkvm::serde::to_vec;
+use shared::{
+    input::{sha256_blocks, Hash, HashInput, Visibility},
+    reference,
+};
 
-pub fn sha(n_thousands: usize) -> impl FnMut() -> (Receipt, Session) {
-    let env = ExecutorEnv::builder()
-        .add_input(&[n_thousands])
-        .build()
-        .unwrap();
+use crate::{env_builder, Job};
 
-    let mut exec = Executor::from_elf(env, SHA_ELF).unwrap();
+/// The guest reads the message from the host and commits its digest
+pub fn input(n_bytes: usize) -> HashInput {
+    HashInput::new(Hash::Sha256, n_bytes, Visibility::Private)
+        .with_compressions(sha256_blocks(n_bytes))
+}
+
+pub fn sha(bytes: &[u8], segment_limit_po2: Option<usize>) -> Job<'static> {
+    // The guest reads the message as words, so the last one is zero padded
+    let mut padded = bytes.to_vec();
+    padded.resize(bytes.len().div_ceil(4) * 4, 0);
 
-    move || {
-        let session = exec.run().unwrap();
-        let receipt = session.prove().unwrap();
+    let mut env = env_builder(segment_limit_po2);
+    env.add_input(&to_vec(&(bytes.len() as u32)).unwrap())
+        .add_input(&padded);
 
-        (receipt, session)
-    }
+    Job::new(&mut env, SHA_ELF, SHA_ID).expect(reference::sha256(bytes))
 }
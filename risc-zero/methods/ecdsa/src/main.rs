@@ -0,0 +1,8 @@
+#![no_main]
+#![no_std]
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    ecdsa_verify::verify();
+}
@@ -0,0 +1,10 @@
+#![no_main]
+#![no_std]
+
+risc0_zkvm::guest::entry!(main);
+
+// The same guest as `ecdsa`, built without risc0's accelerated forks of k256,
+// crypto-bigint and sha2
+pub fn main() {
+    ecdsa_verify::verify();
+}
@@ -0,0 +1,23 @@
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use k256::{
+    ecdsa::{signature::Verifier, Signature, VerifyingKey},
+    EncodedPoint,
+};
+use risc0_zkvm::guest::env;
+
+/// Verifies a secp256k1 ECDSA signature of the SHA-256 of `message`, and
+/// commits the key and message it is valid for
+pub fn verify() {
+    let (key, message, signature): (EncodedPoint, Vec<u8>, Signature) = env::read();
+
+    VerifyingKey::from_encoded_point(&key)
+        .unwrap()
+        .verify(&message, &signature)
+        .expect("the signature is invalid");
+
+    env::commit(&(key, message));
+}
@@ -1,17 +1,33 @@
 #![no_main]
+#![no_std]
 
-use core::hint::black_box;
-use nalgebra::Matrix2;
 use risc0_zkvm::guest::env;
 
 risc0_zkvm::guest::entry!(main);
 
+/// The Goldilocks prime, 2^64 - 2^32 + 1, which miden's field elements are
+/// reduced by too
+const PRIME: u64 = 0xffff_ffff_0000_0001;
+
 pub fn main() {
     let n: u32 = env::read();
-    // Prevent the compiler from optimizing away the computation.
-    black_box(fibonacci(n));
+    env::commit(&fibonacci(n));
 }
 
+/// `n` iterations of `(a, b) = (b, a + b)` modulo [PRIME], starting from `(0, 1)`
 fn fibonacci(n: u32) -> u64 {
-    Matrix2::new(1, 1, 1, 0).pow(n - 1)[(0, 0)]
+    let (mut a, mut b) = (0, 1);
+    for _ in 0..n {
+        (a, b) = (b, add(a, b));
+    }
+    b
+}
+
+fn add(a: u64, b: u64) -> u64 {
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= PRIME {
+        sum.wrapping_sub(PRIME)
+    } else {
+        sum
+    }
 }
@@ -0,0 +1,15 @@
+#![no_main]
+
+use nalgebra::Matrix2;
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    let n: u32 = env::read();
+    env::commit(&fibonacci(n));
+}
+
+fn fibonacci(n: u32) -> u64 {
+    Matrix2::new(1, 1, 1, 0).pow(n - 1)[(0, 0)]
+}
@@ -0,0 +1,25 @@
+#![no_main]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec;
+use risc0_zkvm::guest::env;
+use tiny_keccak::{Hasher, Keccak};
+
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    // The message is packed into words, the last of which is zero padded
+    let n_bytes: u32 = env::read();
+    let mut words = vec![0u32; (n_bytes as usize + 3) / 4];
+    env::read_slice(&mut words);
+    let bytes = &bytemuck::cast_slice::<u32, u8>(&words)[..n_bytes as usize];
+
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    let mut digest = [0u8; 32];
+    hasher.finalize(&mut digest);
+
+    env::commit(&digest);
+}
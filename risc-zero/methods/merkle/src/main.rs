@@ -3,29 +3,63 @@
 
 extern crate alloc;
 
-use core::hint::black_box;
-
-use risc0_zkvm::guest::env;
-use shared::hash::Sha;
-use shared::Tree;
+use alloc::vec::Vec;
+use risc0_zkvm::{guest::env, sha::Digest};
+use shared::hash::{HashFn, Sha};
 
 risc0_zkvm::guest::entry!(main);
 
+/// Leaves are read from the host this many at a time
+const CHUNK: usize = 256;
+
 pub fn main() {
-    let tree1: Tree<Sha> = env::read();
-    let tree2: Tree<Sha> = env::read();
-    let tree = merge_trees(tree1, tree2);
-    black_box(tree);
+    let root1 = stream_root();
+    let root2 = stream_root();
+    env::commit(&Sha::merge(root1, root2));
 }
 
-fn merge_trees(tree1: Tree<Sha>, tree2: Tree<Sha>) -> Tree<Sha> {
-    let hashes = tree1
-        .iter()
-        .chain(tree2.iter())
-        .filter_map(|node| match node {
-            Tree::Leaf(digest) => Some(*digest),
-            Tree::Node { .. } => None,
-        });
+/// Reads a tree's leaf count and then its leaves, merging them as they arrive so
+/// only one subtree root per level is ever kept. A leaf count that isn't a power
+/// of two is padded with null leaves, like `Tree::new`.
+fn stream_root() -> Digest {
+    let n_leaves: u32 = env::read();
+
+    // Subtree roots and their heights, strictly decreasing from the bottom
+    let mut stack: Vec<(Digest, u32)> = Vec::new();
+    let mut chunk = [Sha::null(); CHUNK];
+    let mut remaining = n_leaves as usize;
+    while remaining > 0 {
+        let leaves = &mut chunk[..remaining.min(CHUNK)];
+        env::read_slice(leaves);
+        remaining -= leaves.len();
+
+        for leaf in leaves.iter() {
+            let (mut digest, mut height) = (*leaf, 0);
+            while let Some(&(left, _)) = stack.last().filter(|(_, h)| *h == height) {
+                stack.pop();
+                digest = Sha::merge(left, digest);
+                height += 1;
+            }
+            stack.push((digest, height));
+        }
+    }
+
+    // Pair each leftover subtree with a null one of its height until one is left
+    let mut nulls = Vec::from([Sha::null()]);
+    let (mut digest, mut height) = stack.pop().unwrap();
+    while let Some(&(left, left_height)) = stack.last() {
+        while (nulls.len() as u32) <= height {
+            let null = *nulls.last().unwrap();
+            nulls.push(Sha::merge(null, null));
+        }
+        if left_height == height {
+            stack.pop();
+            digest = Sha::merge(left, digest);
+        } else {
+            digest = Sha::merge(digest, nulls[height as usize]);
+        }
+        height += 1;
+    }
 
-    Tree::new(hashes)
+    digest
 }
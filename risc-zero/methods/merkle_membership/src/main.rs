@@ -4,7 +4,6 @@
 extern crate alloc;
 
 use alloc::vec::Vec;
-use core::hint::black_box;
 use risc0_zkvm::{
     guest::{env, sha::Impl},
     sha::{Digest, Sha256},
@@ -12,17 +11,21 @@ use risc0_zkvm::{
 
 risc0_zkvm::guest::entry!(main);
 
+/// Hashes the leaf, the first digest, up the path of siblings that follow it. Bit
+/// `i` of the leaf's index is set if the running digest is the right child at
+/// level `i`.
 fn main() {
-    let hash_bytes: Vec<u8> = env::read();
-    let hash_words: Vec<u32> = hash_bytes
-        .chunks(4)
-        .map(|slice| u32::from_be_bytes(slice.try_into().unwrap()))
-        .collect();
-    let hash = hash_words
-        .chunks(8)
-        .map(|words| Digest::new(words.try_into().unwrap()))
-        .reduce(|a, b| *<Impl as Sha256>::hash_pair(&a, &b))
-        .unwrap();
+    let index: u64 = env::read();
+    let path: Vec<Digest> = env::read();
+    let mut path = path.into_iter();
+    let leaf = path.next().unwrap();
+    let root = path.enumerate().fold(leaf, |digest, (level, sibling)| {
+        if index >> level & 1 == 0 {
+            *<Impl as Sha256>::hash_pair(&digest, &sibling)
+        } else {
+            *<Impl as Sha256>::hash_pair(&sibling, &digest)
+        }
+    });
 
-    black_box(hash);
+    env::commit(&root);
 }
@@ -0,0 +1,39 @@
+#![no_main]
+#![no_std]
+
+use risc0_zkvm::guest::env;
+use risc0_zkvm_platform::syscall::{bigint, sys_bigint};
+
+risc0_zkvm::guest::entry!(main);
+
+type Words = [u32; bigint::WIDTH_WORDS];
+
+/// Multiplies `x` by `y` modulo `modulus` `n` times, each with one call to the
+/// bigint accelerator. All three are little endian and `x`, `y` < `modulus`.
+pub fn main() {
+    let (x, y, modulus, n): ([u8; 32], [u8; 32], [u8; 32], u32) = env::read();
+    let (y, modulus) = (words(&y), words(&modulus));
+
+    let mut product = words(&x);
+    for _ in 0..n {
+        let mut result = [0; bigint::WIDTH_WORDS];
+        unsafe {
+            sys_bigint(&mut result, bigint::OP_MULTIPLY, &product, &y, &modulus);
+        }
+        product = result;
+    }
+
+    let mut bytes = [0u8; 32];
+    for (chunk, word) in bytes.chunks_mut(4).zip(product) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    env::commit(&bytes);
+}
+
+fn words(bytes: &[u8; 32]) -> Words {
+    let mut words = [0; bigint::WIDTH_WORDS];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    words
+}
@@ -0,0 +1,21 @@
+#![no_main]
+#![no_std]
+
+use crypto_bigint::{Encoding, U256};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+/// Like `modmul`, but in software, with the same algorithm as the accelerator: the
+/// full 512-bit product, then its remainder modulo `modulus`
+pub fn main() {
+    let (x, y, modulus, n): ([u8; 32], [u8; 32], [u8; 32], u32) = env::read();
+    let (y, modulus) = (U256::from_le_bytes(y), U256::from_le_bytes(modulus));
+
+    let mut product = U256::from_le_bytes(x);
+    for _ in 0..n {
+        (product, _) = U256::const_rem_wide(product.mul_wide(&y), &modulus);
+    }
+
+    env::commit(&product.to_le_bytes());
+}
@@ -1,6 +1,9 @@
 #![no_main]
 #![no_std]
 
+extern crate alloc;
+
+use alloc::vec;
 use risc0_zkvm::{
     guest::env,
     sha::{Impl, Sha256},
@@ -8,14 +11,14 @@ use risc0_zkvm::{
 
 risc0_zkvm::guest::entry!(main);
 
-fn bench_sha_n(n_thousands: usize) {
-    let arr = [123u8; 1000];
-    for _ in 0..n_thousands {
-        Impl::hash_bytes(&arr);
-    }
-}
-
 pub fn main() {
-    let n = env::read();
-    bench_sha_n(n);
+    // The message is packed into words, the last of which is zero padded
+    let n_bytes: u32 = env::read();
+    let mut words = vec![0u32; (n_bytes as usize + 3) / 4];
+    env::read_slice(&mut words);
+    let bytes = &bytemuck::cast_slice::<u32, u8>(&words)[..n_bytes as usize];
+
+    let digest: [u8; 32] = Impl::hash_bytes(bytes).as_bytes().try_into().unwrap();
+
+    env::commit(&digest);
 }
@@ -0,0 +1,108 @@
+use serde::Serialize;
+
+/// How the prover receives the input of a benchmark case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Witness data only known to the prover (advice, private inputs, host supplied bytes).
+    Private,
+    /// Part of the statement being proven.
+    Public,
+    /// Baked into the program, so the prover never handles real input.
+    Constant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Hash {
+    Sha256,
+    Sha3_256,
+    Keccak256,
+    Blake3,
+    Rpo256,
+    Pedersen,
+}
+
+/// Describes what a hashing benchmark case actually hashes, so results from
+/// different frameworks can be checked for equivalence before being compared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HashInput {
+    pub hash: Hash,
+    /// Bytes of message data fed to the hash function
+    pub bytes: usize,
+    pub visibility: Visibility,
+    /// Number of compression function (or permutation) calls, if known
+    pub compressions: Option<usize>,
+}
+
+impl HashInput {
+    pub fn new(hash: Hash, bytes: usize, visibility: Visibility) -> Self {
+        Self {
+            hash,
+            bytes,
+            visibility,
+            compressions: None,
+        }
+    }
+
+    pub fn with_compressions(mut self, compressions: usize) -> Self {
+        self.compressions = Some(compressions);
+        self
+    }
+}
+
+/// Number of 64 byte blocks SHA-256 compresses for a padded message of `n_bytes`
+pub fn sha256_blocks(n_bytes: usize) -> usize {
+    // 1 byte for the 0x80 terminator and 8 bytes for the message length
+    (n_bytes + 9).div_ceil(64)
+}
+
+/// Number of 64 byte blocks Blake3 compresses for a message of `n_bytes`
+pub fn blake3_blocks(n_bytes: usize) -> usize {
+    let chunks = usize::max(1, n_bytes.div_ceil(1024));
+    let blocks = usize::max(1, n_bytes.div_ceil(64));
+    // Every parent node in the chunk tree is one more compression
+    blocks + chunks - 1
+}
+
+/// Number of 136 byte blocks Keccak-256 absorbs for a padded message of `n_bytes`,
+/// each one a Keccak-f[1600] permutation
+pub fn keccak256_blocks(n_bytes: usize) -> usize {
+    // At least 1 byte of padding
+    n_bytes / 136 + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_pads_into_another_block_after_55_bytes() {
+        assert_eq!(sha256_blocks(0), 1);
+        assert_eq!(sha256_blocks(55), 1);
+        assert_eq!(sha256_blocks(56), 2);
+        assert_eq!(sha256_blocks(64), 2);
+        assert_eq!(sha256_blocks(119), 2);
+        assert_eq!(sha256_blocks(120), 3);
+    }
+
+    #[test]
+    fn blake3_counts_blocks_and_parent_nodes() {
+        assert_eq!(blake3_blocks(0), 1);
+        assert_eq!(blake3_blocks(64), 1);
+        assert_eq!(blake3_blocks(65), 2);
+        assert_eq!(blake3_blocks(1024), 16);
+        assert_eq!(blake3_blocks(1025), 17 + 1);
+        assert_eq!(blake3_blocks(2048), 32 + 1);
+        assert_eq!(blake3_blocks(3073), 49 + 3);
+    }
+
+    #[test]
+    fn keccak256_pads_into_another_block_after_135_bytes() {
+        assert_eq!(keccak256_blocks(0), 1);
+        assert_eq!(keccak256_blocks(135), 1);
+        assert_eq!(keccak256_blocks(136), 2);
+        assert_eq!(keccak256_blocks(271), 2);
+        assert_eq!(keccak256_blocks(272), 3);
+    }
+}
@@ -1,6 +1,8 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "report")]
+extern crate std;
 
 use alloc::{boxed::Box, string::String, vec::Vec};
 use hash::HashFn;
@@ -9,6 +11,12 @@ use serde::{Deserialize, Serialize};
 use core::iter::once;
 
 pub mod hash;
+pub mod input;
+#[cfg(feature = "std")]
+pub mod reference;
+#[cfg(feature = "report")]
+pub mod report;
+pub mod security;
 
 #[cfg(feature = "std")]
 pub use fastrand;
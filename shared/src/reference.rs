@@ -0,0 +1,211 @@
+//! Native implementations of the benchmark workloads. Each framework's output is
+//! compared against these, so a benchmark fails rather than reporting the time it
+//! took to compute the wrong answer.
+
+use core::ops::Add;
+use risc0_zkvm::sha::{self, Sha256};
+
+use crate::hash::HashFn;
+
+/// Runs `n` iterations of `(a, b) = (b, a + b)` starting from `(0, 1)` and returns `b`,
+/// in whichever field (or wrapping integer) `T` is.
+pub fn fib<T: Copy + Add<Output = T>>(n: usize, zero: T, one: T) -> T {
+    let (mut a, mut b) = (zero, one);
+    for _ in 0..n {
+        (a, b) = (b, a + b);
+    }
+
+    b
+}
+
+pub fn sha256(bytes: &[u8]) -> [u8; 32] {
+    sha::Impl::hash_bytes(bytes).as_bytes().try_into().unwrap()
+}
+
+pub fn blake3(bytes: &[u8]) -> [u8; 32] {
+    *blake3::hash(bytes).as_bytes()
+}
+
+/// The original Keccak-256 used by Ethereum, with 0x01 padding rather than SHA3-256's 0x06
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    use sha3::Digest;
+
+    sha3::Keccak256::digest(bytes).into()
+}
+
+/// Multiplies `x` by `y` modulo `modulus` `n` times. All three are little endian.
+pub fn modmul(x: &[u8; 32], y: &[u8; 32], modulus: &[u8; 32], n: usize) -> [u8; 32] {
+    use crypto_bigint::{Encoding, U256};
+
+    let (y, modulus) = (U256::from_le_bytes(*y), U256::from_le_bytes(*modulus));
+    let mut product = U256::from_le_bytes(*x);
+    for _ in 0..n {
+        (product, _) = U256::const_rem_wide(product.mul_wide(&y), &modulus);
+    }
+
+    product.to_le_bytes()
+}
+
+/// Hashes `leaf` with each sibling in `path` in turn. Bit `i` of `index`, the leaf's
+/// position in the tree, is set if the running digest is the right child at level `i`.
+pub fn merkle_path<H: HashFn>(leaf: H::Digest, index: u64, path: &[H::Digest]) -> H::Digest {
+    path.iter()
+        .enumerate()
+        .fold(leaf, |digest, (level, sibling)| {
+            if index >> level & 1 == 0 {
+                H::merge(digest, *sibling)
+            } else {
+                H::merge(*sibling, digest)
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::String, vec, vec::Vec};
+    use core::num::Wrapping;
+    use crypto_bigint::{Encoding, U256};
+
+    use super::*;
+    use crate::{hash::Sha, Tree};
+
+    /// `n` bytes counting up modulo 251, like the BLAKE3 test vectors
+    fn message(n: usize) -> Vec<u8> {
+        (0..n).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn fib_counts_iterations() {
+        assert_eq!(fib(0, 0u64, 1), 1);
+        assert_eq!(fib(1, 0u64, 1), 1);
+        assert_eq!(fib(10, 0u64, 1), 89);
+        assert_eq!(
+            fib(93, Wrapping(0u64), Wrapping(1)),
+            Wrapping(19740274219868223167u128 as u64)
+        );
+    }
+
+    #[test]
+    fn sha256_matches_known_digests() {
+        for (n, digest) in [
+            (
+                0,
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                55,
+                "463eb28e72f82e0a96c0a4cc53690c571281131f672aa229e0d45ae59b598b59",
+            ),
+            (
+                56,
+                "da2ae4d6b36748f2a318f23e7ab1dfdf45acdc9d049bd80e59de82a60895f562",
+            ),
+            (
+                64,
+                "fdeab9acf3710362bd2658cdc9a29e8f9c757fcf9811603a8c447cd1d9151108",
+            ),
+        ] {
+            assert_eq!(hex(&sha256(&message(n))), digest, "{n} bytes");
+        }
+    }
+
+    #[test]
+    fn blake3_matches_known_digests() {
+        for (n, digest) in [
+            (
+                0,
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            ),
+            (
+                1024,
+                "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+            ),
+            (
+                1025,
+                "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+            ),
+            (
+                2048,
+                "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a",
+            ),
+            (
+                3073,
+                "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3",
+            ),
+        ] {
+            assert_eq!(hex(&blake3(&message(n))), digest, "{n} bytes");
+        }
+    }
+
+    #[test]
+    fn keccak256_matches_known_digests() {
+        for (n, digest) in [
+            (
+                0,
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            ),
+            (
+                135,
+                "cbdfd9dee5faad3818d6b06f95a219fd290b0e1706f6a82e5a595b9ce9faca62",
+            ),
+            (
+                136,
+                "7ce759f1ab7f9ce437719970c26b0a66ff11fe3e38e17df89cf5d29c7d7f807e",
+            ),
+        ] {
+            assert_eq!(hex(&keccak256(&message(n))), digest, "{n} bytes");
+        }
+    }
+
+    #[test]
+    fn modmul_reduces_every_product() {
+        let small = |n: u64| U256::from_u64(n).to_le_bytes();
+        assert_eq!(modmul(&small(3), &small(5), &small(7), 0), small(3));
+        assert_eq!(modmul(&small(3), &small(5), &small(7), 1), small(1));
+        assert_eq!(modmul(&small(3), &small(5), &small(7), 2), small(5));
+
+        // -1 squared is 1 modulo the secp256k1 prime
+        let p =
+            U256::from_be_hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F");
+        let minus_one = p.wrapping_sub(&U256::ONE).to_le_bytes();
+        assert_eq!(
+            modmul(&minus_one, &minus_one, &p.to_le_bytes(), 1),
+            small(1)
+        );
+        assert_eq!(
+            modmul(&minus_one, &minus_one, &p.to_le_bytes(), 2),
+            minus_one
+        );
+    }
+
+    #[test]
+    fn merkle_path_reaches_the_root_from_every_leaf() {
+        let leaves = core::iter::repeat_with(Sha::random)
+            .take(8)
+            .collect::<Vec<_>>();
+        let mut levels = vec![leaves.clone()];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| Sha::merge(pair[0], pair[1]));
+            levels.push(level.collect());
+        }
+        let root = levels.last().unwrap()[0];
+        assert_eq!(root, Tree::<Sha>::new(leaves.clone()).digest());
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let path = levels[..levels.len() - 1]
+                .iter()
+                .enumerate()
+                .map(|(level, digests)| digests[(index >> level) ^ 1])
+                .collect::<Vec<_>>();
+            assert_eq!(merkle_path::<Sha>(*leaf, index as u64, &path), root);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Benchy only records numeric metrics, so anything else we want to know about a
+//! benchmark case is written to `<framework>.meta.json` next to benchy's output,
+//! where `combine.py` merges it into the results.
+
+use alloc::{format, string::String};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{env, fs, path::PathBuf};
+
+/// Like benchy, we only write results when `BENCHY_OUTPUT_DIR` is set
+fn path(framework: &str) -> Option<PathBuf> {
+    let dir = env::var("BENCHY_OUTPUT_DIR").ok()?;
+    Some(PathBuf::from(dir).join(format!("{framework}.meta.json")))
+}
+
+/// Deletes the framework's metadata from an earlier run, so cases that have since
+/// been removed or renamed don't carry over. benchy forks for every run, so this
+/// has to happen in `main` before any benchmark, by declaring `benchy::main!`
+/// inside a `main` of our own and calling it:
+///
+/// ```ignore
+/// fn main() {
+///     report::reset("miden");
+///     benchy::main!("miden", fibonacci, sha256);
+///     main();
+/// }
+/// ```
+pub fn reset(framework: &str) {
+    if let Some(path) = path(framework) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Records `value` under `key` for one case of a benchmark, e.g.
+/// `record("miden", "SHA256", &b.name, "input", &input)`.
+pub fn record(framework: &str, benchmark: &str, case: &str, key: &str, value: &impl Serialize) {
+    let Some(path) = path(framework) else {
+        return;
+    };
+    let mut meta: Map<String, Value> = fs::read(&path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+
+    let cases = meta
+        .entry(benchmark)
+        .or_insert_with(|| Value::Object(Map::new()));
+    let case = cases
+        .as_object_mut()
+        .unwrap()
+        .entry(case)
+        .or_insert_with(|| Value::Object(Map::new()));
+    case.as_object_mut()
+        .unwrap()
+        .insert(key.into(), serde_json::to_value(value).unwrap());
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).unwrap();
+    }
+    fs::write(&path, serde_json::to_vec_pretty(&meta).unwrap()).unwrap();
+}
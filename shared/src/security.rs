@@ -0,0 +1,49 @@
+use serde::Serialize;
+
+/// What a proof's security rests on. Levels of different kinds aren't comparable,
+/// e.g. a STARK's FRI soundness conjecture and a SNARK curve's discrete log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Assumption {
+    /// The soundness of FRI, i.e. of STARKs, up to its proximity gap conjectures
+    ConjecturedFri,
+    /// The hardness of discrete log in the group of the proof system's curve
+    DiscreteLog,
+}
+
+/// The security level a proof claims, in bits. Frameworks are only compared
+/// fairly when their proofs target the same level under the same assumption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Security {
+    pub kind: Assumption,
+    /// Relies on conjectures about the soundness of the protocol (e.g. FRI
+    /// proximity gaps, or the best known attacks on discrete log in the curve's group)
+    pub conjectured: Option<u32>,
+    /// Provable from the protocol parameters alone
+    pub proven: Option<u32>,
+}
+
+impl Security {
+    /// A STARK's conjectured security
+    pub fn fri(bits: u32) -> Self {
+        Self {
+            kind: Assumption::ConjecturedFri,
+            conjectured: Some(bits),
+            proven: None,
+        }
+    }
+
+    /// A SNARK's security, estimated from the best known discrete log attacks on its curve
+    pub fn discrete_log(bits: u32) -> Self {
+        Self {
+            kind: Assumption::DiscreteLog,
+            conjectured: Some(bits),
+            proven: None,
+        }
+    }
+
+    pub fn with_proven(mut self, bits: u32) -> Self {
+        self.proven = Some(bits);
+        self
+    }
+}